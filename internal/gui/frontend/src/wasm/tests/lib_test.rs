@@ -5,7 +5,40 @@ mod tests {
     use wasm_bindgen::JsValue;
     
     // Import the crate functions directly
-    use log_engine::{merge_insert_logs, get_memory_usage, force_garbage_collection};
+    use log_engine::{merge_insert_logs, merge_insert_logs_many, coalesce_repeats,
+                     find_nearest_log_by_time, set_diagnostic_sink, filter_logs_containing,
+                     position_accumulator_new, position_accumulator_push,
+                     position_accumulator_total, position_accumulator_free,
+                     position_accumulator_corrected, filter_logs_by, set_bigint_mode,
+                     set_preserve_complex_fields, compute_total_height, assign_sequences,
+                     merge_insert_logs_cmp, record_memory_sample, get_memory_history,
+                     find_time_mismatches, logs_after_sequence, would_operation_fit_with_overhead,
+                     get_memory_usage_lite, compute_level_time_ranges, merge_insert_logs_trusted,
+                     truncate_messages, find_sequence_gaps, get_allocation_stats,
+                     reset_all_allocation_stats, ensure_sufficient_memory,
+                     reset_internal_allocation_stats, merge_insert_logs_upsert, count_logs,
+                     merge_and_get_chunk, clear_log_cache, estimate_memory_for_batch,
+                     merge_insert_logs_stats_only, tail_logs, set_max_message_bytes,
+                     normalize_single_time, histogram_by_time, set_cache_order,
+                     drop_adjacent_duplicates, top_largest_logs, simd_enabled,
+                     merge_insert_logs_tagged, set_average_window, peek_bounds, set_height_clamp,
+                     serialize_logs_binary, deserialize_logs_binary, detect_clock_regressions,
+                     append_only_newer, set_compact_output, find_logs_with_behavior,
+                     distinct_behaviors, merge_insert_logs_reject_skew, scroll_position_for_index,
+                     merge_dedup_last_wins, downsample_logs, merge_insert_logs_reserved,
+                     classify_behavior, set_emit_behavior_kind, verify_merge_invariants,
+                     set_growth_callback, estimate_remaining_log_capacity, sort_logs_by_field,
+                     merge_and_report_inserts, count_logs_containing, set_memory_soft_cap_bytes,
+                     set_min_growth_pages, get_memory_usage, reverse_logs, sanitize_messages,
+                     set_profiling, get_last_merge_timings, search_logs_multi,
+                     export_filtered_ndjson, set_field_aliases, cache_logs, merge_caches,
+                     get_cache_chunk, free_cache, find_exact_message, merge_insert_logs_pinned,
+                     estimate_single_log_bytes, merge_near_duplicates, next_ingestion_time,
+                     reset_ingestion_clock, split_by_source, try_ensure_memory, get_tracker_drift,
+                     merge_insert_logs_with_error_rate, register_level, clear_registered_levels,
+                     set_buffer_reuse, index_of_sequence, find_invalid_utf8,
+                     measure_logs_serialized_bytes, set_verbose_logging, distinct_field_values,
+                     set_large_merge_threshold};
 
     #[wasm_bindgen_test]
     fn test_empty_arrays() {
@@ -72,6 +105,54 @@ mod tests {
         }
     }
 
+    #[wasm_bindgen_test]
+    fn test_large_merge_order() {
+        // Timestamps interleave (even/odd seconds) and collide every 1,000 entries
+        // so the sequence tie-breaker is exercised too.
+        const COUNT: u32 = 11_000;
+        let existing = js_sys::Array::new();
+        let new = js_sys::Array::new();
+        let mut expected: Vec<(f64, u32)> = Vec::with_capacity(2 * COUNT as usize);
+        for i in 0..COUNT {
+            let existing_time = (2 * i) as f64;
+            let new_time = if i % 1000 == 0 { existing_time } else { (2 * i + 1) as f64 };
+
+            existing.push(&create_log_with_sequence(existing_time, 2 * i));
+            new.push(&create_log_with_sequence(new_time, 2 * i + 1));
+            expected.push((existing_time * 1000.0, 2 * i));
+            expected.push((new_time * 1000.0, 2 * i + 1));
+        }
+
+        let result = merge_insert_logs(existing.into(), new.into()).unwrap();
+        let result_array = js_sys::Array::from(&result);
+        assert_eq!(result_array.length(), 2 * COUNT);
+
+        // By time, then sequence
+        expected.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)));
+        for (i, (time, sequence)) in expected.iter().enumerate() {
+            let log = result_array.get(i as u32);
+            assert_eq!(get_unix_time_from_log(&log), *time, "Wrong timestamp at index {}", i);
+            assert_eq!(get_sequence_from_log(&log), *sequence, "Wrong sequence at index {}", i);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_allocations_independent_of_size() {
+        let allocation_count = || -> f64 {
+            js_sys::Map::from(get_allocation_stats()).get(&"allocation_count".into()).as_f64().unwrap()
+        };
+        // Small and large merges take the same path: the two inputs and the result buffer
+        for count in [10, 11_000] {
+            merge_insert_logs(create_sorted_logs(0, count).into(), create_sorted_logs(count, count).into()).unwrap();
+            assert_eq!(allocation_count(), 3.0, "Unexpected allocations merging {} entries", count);
+        }
+
+        // The old cutoff no longer changes anything
+        set_large_merge_threshold(0);
+        merge_insert_logs(create_sorted_logs(0, 10).into(), create_sorted_logs(10, 10).into()).unwrap();
+        assert_eq!(allocation_count(), 3.0);
+    }
+
     #[wasm_bindgen_test]
     fn test_sequence_tie_breaker() {
         // Create logs with same timestamps but different sequences
@@ -96,6 +177,2370 @@ mod tests {
     }
 
     #[wasm_bindgen_test]
+    fn test_merge_many_arrays() {
+        // Three interleaved batches plus an empty one
+        let batches = js_sys::Array::new();
+        batches.push(&create_logs_with_timestamps(&[1.0, 4.0, 7.0]));
+        batches.push(&js_sys::Array::new());
+        batches.push(&create_logs_with_timestamps(&[2.0, 5.0, 8.0]));
+        batches.push(&create_logs_with_timestamps(&[3.0, 6.0]));
+
+        let result = merge_insert_logs_many(batches.into()).unwrap();
+        let result_array = js_sys::Array::from(&result);
+        assert_eq!(result_array.length(), 8);
+
+        for i in 0..8 {
+            let time = get_unix_time_from_log(&result_array.get(i));
+            assert_eq!(time, (i + 1) as f64 * 1000.0, "Logs not in chronological order");
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_nearest_log_by_time() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 4.0]);
+
+        assert_eq!(find_nearest_log_by_time(logs.clone().into(), 0.0), 0);
+        assert_eq!(find_nearest_log_by_time(logs.clone().into(), 2100.0), 1);
+        assert_eq!(find_nearest_log_by_time(logs.clone().into(), 3500.0), 2);
+        // Equidistant: the earlier entry wins
+        assert_eq!(find_nearest_log_by_time(logs.clone().into(), 3000.0), 1);
+        assert_eq!(find_nearest_log_by_time(logs.into(), 9000.0), 2);
+
+        assert_eq!(find_nearest_log_by_time(js_sys::Array::new().into(), 1.0), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_filter_case_folding() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0, 4.0]);
+        for (i, message) in ["Error: Timeout", "error: timeout", "ÉCHEC réseau", "échec réseau"].iter().enumerate() {
+            let log = logs.get(i as u32);
+            js_sys::Reflect::set(&log, &"message".into(), &(*message).into()).unwrap();
+        }
+        let count = |needle: &str, case_insensitive: bool| {
+            let result = filter_logs_containing(logs.clone().into(), needle, case_insensitive).unwrap();
+            js_sys::Array::from(&result).length()
+        };
+
+        // ASCII letters fold when case-insensitive
+        assert_eq!(count("ERROR", false), 0);
+        assert_eq!(count("ERROR", true), 2);
+        assert_eq!(count("timeout", false), 1);
+
+        // Non-ASCII letters never fold, but ASCII letters around them still do
+        assert_eq!(count("échec", true), 1);
+        assert_eq!(count("ÉCHEC", true), 1);
+        assert_eq!(count("RÉSEAU", true), 0);
+        assert_eq!(count("Réseau", true), 2);
+
+        // An empty needle keeps everything
+        assert_eq!(count("", true), 4);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_position_accumulator() {
+        let handle = position_accumulator_new(25.0, 2.0);
+
+        assert_eq!(position_accumulator_push(handle, 1, 30.0), 0.0);
+        assert_eq!(position_accumulator_push(handle, 2, 40.0), 32.0);
+        // Invalid height falls back to the average
+        assert_eq!(position_accumulator_push(handle, 3, -5.0), 74.0);
+        // Re-pushing a known sequence keeps its position and is not re-counted
+        assert_eq!(position_accumulator_push(handle, 2, 99.0), 32.0);
+        assert_eq!(position_accumulator_total(handle), 101.0);
+        assert_eq!(position_accumulator_corrected(handle).to_vec(), vec![3]);
+
+        assert!(position_accumulator_free(handle));
+        assert_eq!(position_accumulator_total(handle), -1.0);
+        assert_eq!(position_accumulator_push(handle, 4, 10.0), -1.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_compute_total_height() {
+        let logs = js_sys::Array::new();
+        for sequence in 1..=3 {
+            logs.push(&create_log_with_sequence(1.0, sequence));
+        }
+        let heights = js_sys::Object::new();
+        js_sys::Reflect::set(&heights, &JsValue::from(1), &JsValue::from(30.0)).unwrap();
+        js_sys::Reflect::set(&heights, &JsValue::from(2), &JsValue::from(40.0)).unwrap();
+        js_sys::Reflect::set(&heights, &JsValue::from(3), &JsValue::from(-5.0)).unwrap();
+
+        // Same rows as test_position_accumulator, so the totals must agree
+        let total = compute_total_height(logs.clone().into(), heights.into(), 25.0, 2.0).unwrap();
+        assert_eq!(total, 101.0);
+
+        // Without measurements every row uses the clamped average
+        let total = compute_total_height(logs.into(), JsValue::NULL, 500.0, 0.0).unwrap();
+        assert_eq!(total, 300.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_assign_sequences() {
+        let logs = js_sys::Array::new();
+        logs.push(&create_log_with_timestamp(1000.0));
+        logs.push(&create_log_with_sequence(1.0, 7));
+        logs.push(&create_log_with_timestamp(1000.0));
+
+        let result = assign_sequences(logs.into(), 100).unwrap();
+        let result_logs = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        assert_eq!(get_sequence_from_log(&result_logs.get(0)), 100);
+        assert_eq!(get_sequence_from_log(&result_logs.get(1)), 7);
+        assert_eq!(get_sequence_from_log(&result_logs.get(2)), 101);
+
+        let next_sequence = js_sys::Reflect::get(&result, &"next_sequence".into()).unwrap();
+        assert_eq!(next_sequence.as_f64(), Some(102.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_with_comparator() {
+        let with_priority = |time: f64, priority: i32| {
+            let log = create_log_with_timestamp(time);
+            js_sys::Reflect::set(&log, &"priority".into(), &priority.into()).unwrap();
+            log
+        };
+        let existing = js_sys::Array::new();
+        existing.push(&with_priority(1000.0, 3));
+        existing.push(&with_priority(2000.0, 1));
+        let new = js_sys::Array::new();
+        new.push(&with_priority(3000.0, 2));
+        new.push(&with_priority(4000.0, 1));
+
+        // Highest priority first, ignoring time
+        let cmp = js_sys::Function::new_with_args("a, b", "return b.priority - a.priority");
+        let result = merge_insert_logs_cmp(existing.clone().into(), new.clone().into(), cmp.into()).unwrap();
+        let result_array = js_sys::Array::from(&result);
+        let times: Vec<f64> = result_array.iter().map(|log| get_unix_time_from_log(&log)).collect();
+        // Equal priorities keep existing entries ahead of new ones
+        assert_eq!(times, vec![1000.0, 3000.0, 2000.0, 4000.0]);
+
+        // Without a comparator the native time ordering applies
+        let result = merge_insert_logs_cmp(existing.into(), new.into(), JsValue::UNDEFINED).unwrap();
+        let times: Vec<f64> = js_sys::Array::from(&result).iter().map(|log| get_unix_time_from_log(&log)).collect();
+        assert_eq!(times, vec![1000.0, 2000.0, 3000.0, 4000.0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_comparator_merge_emits_compared_objects() {
+        let unsequenced = |times: &[f64]| -> js_sys::Array {
+            let logs = create_logs_with_timestamps(times);
+            for log_item in logs.iter() {
+                js_sys::Reflect::delete_property(&log_item.into(), &"_sequence".into()).unwrap();
+            }
+            logs
+        };
+        // Newest first, marking every object it is shown
+        let cmp = js_sys::Function::new_with_args("a, b", "a.compared = b.compared = true; return b._unix_time - a._unix_time");
+        let sequences = |logs: &JsValue| -> Vec<u32> {
+            js_sys::Array::from(logs).iter().enumerate().map(|(position, log)| {
+                js_sys::Reflect::get(&log, &"_sequence".into()).unwrap().as_f64().map_or(position as u32, |sequence| sequence as u32)
+            }).collect()
+        };
+
+        for compact in [false, true] {
+            set_compact_output(compact);
+            let result = merge_insert_logs_cmp(unsequenced(&[1.0, 2.0]).into(), unsequenced(&[3.0, 4.0]).into(), cmp.clone().into());
+            set_compact_output(false);
+            let result = result.unwrap();
+
+            for log in js_sys::Array::from(&result).iter() {
+                assert_eq!(js_sys::Reflect::get(&log, &"compared".into()).unwrap(), JsValue::TRUE);
+            }
+            // The fallback sequences are those the comparator saw, not the output positions
+            assert_eq!(sequences(&result), vec![3, 2, 1, 0], "compact: {}", compact);
+        }
+
+        // An empty side hands back the other input without calling the comparator
+        let existing = unsequenced(&[2.0, 1.0]);
+        let result = merge_insert_logs_cmp(existing.clone().into(), js_sys::Array::new().into(), cmp.into()).unwrap();
+        assert!(js_sys::Object::is(&result, &existing));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_memory_history_is_capped() {
+        for _ in 0..305 {
+            record_memory_sample();
+        }
+
+        let history = js_sys::Array::from(&get_memory_history());
+        assert_eq!(history.length(), 300);
+
+        let newest = history.get(299);
+        let total_bytes = js_sys::Reflect::get(&newest, &"total_bytes".into()).unwrap().as_f64().unwrap();
+        let used_bytes = js_sys::Reflect::get(&newest, &"used_bytes".into()).unwrap().as_f64().unwrap();
+        assert!(total_bytes > 0.0);
+        assert!(used_bytes <= total_bytes);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_time_mismatches() {
+        // Format the expected local time for a timestamp the same way the check does
+        let local_time = |unix_time: f64| {
+            let date = js_sys::Date::new(&JsValue::from_f64(unix_time * 1000.0));
+            format!("{:02}:{:02}:{:02}", date.get_hours(), date.get_minutes(), date.get_seconds())
+        };
+        let logs = js_sys::Array::new();
+        for (unix_time, time) in [(1000.0, local_time(1000.0)),
+                                  (2000.0, local_time(2002.0)),
+                                  (3000.0, local_time(3600.0))] {
+            let log = create_log_with_timestamp(unix_time);
+            js_sys::Reflect::set(&log, &"time".into(), &time.into()).unwrap();
+            logs.push(&log);
+        }
+        // Entries without a time field are skipped
+        let untimed = create_log_with_timestamp(4000.0);
+        js_sys::Reflect::delete_property(&untimed, &"time".into()).unwrap();
+        logs.push(&untimed);
+
+        let result = find_time_mismatches(logs.into(), 5.0).unwrap();
+        let indices: Vec<f64> = js_sys::Array::from(&result).iter().filter_map(|i| i.as_f64()).collect();
+        assert_eq!(indices, vec![2.0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_logs_after_sequence() {
+        let logs = js_sys::Array::new();
+        logs.push(&create_log_with_timestamp(500.0)); // No sequence, counts as 0
+        for sequence in [3, 5, 8, 9] {
+            logs.push(&create_log_with_sequence(sequence as f64, sequence));
+        }
+
+        let result = js_sys::Array::from(&logs_after_sequence(logs.clone().into(), 5).unwrap());
+        let sequences: Vec<u32> = result.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![8, 9]);
+
+        let result = js_sys::Array::from(&logs_after_sequence(logs.clone().into(), 0).unwrap());
+        assert_eq!(result.length(), 4);
+
+        let result = js_sys::Array::from(&logs_after_sequence(logs.into(), 9).unwrap());
+        assert_eq!(result.length(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_would_operation_fit_with_overhead() {
+        let usage = get_memory_usage_lite();
+        let used_bytes = js_sys::Reflect::get(&usage, &"used_bytes".into()).unwrap().as_f64().unwrap() as usize;
+        let heap_size = used_bytes + 1500;
+
+        assert!(would_operation_fit_with_overhead(1000, heap_size, 1.2));
+        assert!(!would_operation_fit_with_overhead(1000, heap_size, 2.0));
+        // Ratios below 1.0 are clamped, never shrinking the estimate
+        assert!(!would_operation_fit_with_overhead(2000, heap_size, 0.5));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_compute_level_time_ranges() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0, 4.0]);
+        for (i, level) in ["ERROR", "INFO", "ERROR", "ERROR"].iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(i as u32), &"level".into(), &(*level).into()).unwrap();
+        }
+        // Counted but outside the time span
+        js_sys::Reflect::delete_property(&js_sys::Object::from(logs.get(3)), &"_unix_time".into()).unwrap();
+
+        let result = js_sys::Map::from(compute_level_time_ranges(logs.into()).unwrap());
+        let errors = js_sys::Map::from(result.get(&"ERROR".into()));
+        assert_eq!(errors.get(&"count".into()).as_f64(), Some(3.0));
+        assert_eq!(errors.get(&"first_unix".into()).as_f64(), Some(1000.0));
+        assert_eq!(errors.get(&"last_unix".into()).as_f64(), Some(3000.0));
+
+        let infos = js_sys::Map::from(result.get(&"INFO".into()));
+        assert_eq!(infos.get(&"count".into()).as_f64(), Some(1.0));
+        assert_eq!(infos.get(&"first_unix".into()).as_f64(), Some(2000.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_trusted_merge_matches_standard_merge() {
+        let existing = js_sys::Array::new();
+        let new = js_sys::Array::new();
+        for (sequence, time) in [1.0, 2.0, 2.0, 5.0].iter().enumerate() {
+            existing.push(&create_log_with_sequence(*time, sequence as u32));
+        }
+        for (sequence, time) in [0.5, 2.0, 3.0, 6.0].iter().enumerate() {
+            new.push(&create_log_with_sequence(*time, 10 + sequence as u32));
+        }
+
+        let order = |result: JsValue| -> Vec<(f64, u32)> {
+            js_sys::Array::from(&result).iter()
+                .map(|log| (get_unix_time_from_log(&log), get_sequence_from_log(&log)))
+                .collect()
+        };
+        let standard = order(merge_insert_logs(existing.clone().into(), new.clone().into()).unwrap());
+        let trusted = order(merge_insert_logs_trusted(existing.clone().into(), new.into()).unwrap());
+        assert_eq!(trusted, standard);
+        assert_eq!(trusted.len(), 8);
+
+        // An empty side hands back the other input, as merge_insert_logs does
+        let unchanged = merge_insert_logs_trusted(JsValue::NULL, existing.clone().into()).unwrap();
+        assert!(js_sys::Object::is(&unchanged, &existing));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_truncate_messages() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0]);
+        // 'é' is two bytes, so a cut at 4 bytes has to step back to 3
+        js_sys::Reflect::set(&logs.get(0), &"message".into(), &"abcéfgh".into()).unwrap();
+        js_sys::Reflect::set(&logs.get(1), &"message".into(), &"abc".into()).unwrap();
+
+        let result = js_sys::Array::from(&truncate_messages(logs.into(), 4).unwrap());
+        let truncated = result.get(0);
+        let message = js_sys::Reflect::get(&truncated, &"message".into()).unwrap().as_string().unwrap();
+        assert_eq!(message, "abc… (truncated 5 bytes)");
+        let original_len = js_sys::Reflect::get(&truncated, &"_original_message_len".into()).unwrap();
+        assert_eq!(original_len.as_f64(), Some(8.0));
+
+        let untouched = result.get(1);
+        assert_eq!(js_sys::Reflect::get(&untouched, &"message".into()).unwrap().as_string().as_deref(), Some("abc"));
+        assert!(!js_sys::Reflect::has(&untouched, &"_original_message_len".into()).unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_sequence_gaps() {
+        let logs = js_sys::Array::new();
+        logs.push(&create_log_with_sequence(1.0, 1));
+        logs.push(&create_log_with_sequence(2.0, 2));
+        logs.push(&create_log_with_timestamp(2500.0)); // No sequence, ignored
+        logs.push(&create_log_with_sequence(3.0, 5));
+        logs.push(&create_log_with_sequence(4.0, 5));
+        logs.push(&create_log_with_sequence(5.0, 7));
+
+        let gaps = js_sys::Array::from(&find_sequence_gaps(logs.into()).unwrap());
+        let describe = |gap: JsValue| -> Vec<f64> {
+            ["after_index", "missing_from", "missing_to"].iter()
+                .map(|key| js_sys::Reflect::get(&gap, &(*key).into()).unwrap().as_f64().unwrap())
+                .collect()
+        };
+        assert_eq!(gaps.length(), 2);
+        assert_eq!(describe(gaps.get(0)), vec![1.0, 3.0, 4.0]);
+        assert_eq!(describe(gaps.get(1)), vec![4.0, 6.0, 6.0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_partial_and_full_allocation_resets() {
+        let stat = |key: &str| js_sys::Map::from(get_allocation_stats()).get(&key.into()).as_f64().unwrap();
+
+        // Record allocations, then force a growth with a request well above the free space
+        merge_insert_logs(create_test_logs(50).into(), create_test_logs(50).into()).unwrap();
+        let usage = get_memory_usage_lite();
+        let total_bytes = js_sys::Reflect::get(&usage, &"total_bytes".into()).unwrap().as_f64().unwrap() as usize;
+        // Pretend growth succeeds without actually growing the test's memory
+        let memory = wasm_bindgen::memory();
+        js_sys::Reflect::set(&memory, &"grow".into(), &js_sys::Function::new_no_args("return 0;")).unwrap();
+        let grown = ensure_sufficient_memory(total_bytes);
+        js_sys::Reflect::delete_property(&memory.into(), &"grow".into()).unwrap();
+        assert!(grown);
+        assert!(stat("growth_events") > 0.0);
+
+        let peak_bytes = stat("peak_bytes");
+        let average_allocation = stat("average_allocation");
+        let growth_events = stat("growth_events");
+        let growth_failures = stat("growth_failures");
+        assert!(peak_bytes > 0.0);
+
+        // The partial reset only clears the current operation's counters
+        reset_internal_allocation_stats();
+        assert_eq!(stat("active_bytes"), 0.0);
+        assert_eq!(stat("allocation_count"), 0.0);
+        assert_eq!(stat("peak_bytes"), peak_bytes);
+        assert_eq!(stat("average_allocation"), average_allocation);
+        assert_eq!(stat("growth_events"), growth_events);
+        assert_eq!(stat("growth_failures"), growth_failures);
+
+        // The full reset clears history as well
+        reset_all_allocation_stats();
+        for key in ["active_bytes", "peak_bytes", "allocation_count", "average_allocation",
+                    "growth_events", "growth_failures"] {
+            assert_eq!(stat(key), 0.0, "{} was not reset", key);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_upsert_replaces_by_sequence() {
+        let existing = js_sys::Array::new();
+        existing.push(&create_log_with_sequence(1.0, 1));
+        existing.push(&create_log_with_sequence(2.0, 2));
+        let new = js_sys::Array::new();
+        let retried = create_log_with_sequence(2.0, 2);
+        js_sys::Reflect::set(&retried, &"message".into(), &"retried".into()).unwrap();
+        new.push(&retried);
+        new.push(&create_log_with_sequence(3.0, 3));
+
+        let result = js_sys::Array::from(&merge_insert_logs_upsert(existing.clone().into(), new.clone().into()).unwrap());
+        let sequences: Vec<u32> = result.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![1, 2, 3]);
+        let message = js_sys::Reflect::get(&result.get(1), &"message".into()).unwrap();
+        assert_eq!(message.as_string().as_deref(), Some("retried"));
+
+        // Nothing to apply: existing comes back as given
+        let unchanged = merge_insert_logs_upsert(existing.clone().into(), JsValue::NULL).unwrap();
+        assert!(js_sys::Object::is(&unchanged, &existing));
+        // With no existing entries the new ones still replace each other
+        new.push(&create_log_with_sequence(4.0, 3));
+        let result = js_sys::Array::from(&merge_insert_logs_upsert(js_sys::Array::new().into(), new.into()).unwrap());
+        let sequences: Vec<u32> = result.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![2, 3]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_count_logs() {
+        assert_eq!(count_logs(&create_test_logs(7).into()), 7);
+        assert_eq!(count_logs(&js_sys::Array::new().into()), 0);
+        assert_eq!(count_logs(&js_sys::Object::new().into()), 0);
+        assert_eq!(count_logs(&JsValue::NULL), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_and_get_chunk() {
+        let existing: JsValue = create_logs_with_timestamps(&[1.0, 3.0, 5.0]).into();
+        let new_array = create_logs_with_timestamps(&[2.0, 4.0]);
+        let new: JsValue = new_array.clone().into();
+        let read_chunk = |index: usize| {
+            let chunk = merge_and_get_chunk(existing.clone(), new.clone(), index, 2).unwrap();
+            let logs = js_sys::Array::from(&js_sys::Reflect::get(&chunk, &"logs".into()).unwrap());
+            let times: Vec<f64> = logs.iter().map(|log| get_unix_time_from_log(&log)).collect();
+            let has_more = js_sys::Reflect::get(&chunk, &"has_more".into()).unwrap().as_bool().unwrap();
+            let total = js_sys::Reflect::get(&chunk, &"total".into()).unwrap().as_f64().unwrap();
+            (times, has_more, total)
+        };
+
+        assert_eq!(read_chunk(0), (vec![1000.0, 2000.0], true, 5.0));
+        assert_eq!(read_chunk(1), (vec![3000.0, 4000.0], true, 5.0));
+        assert_eq!(read_chunk(2), (vec![5000.0], false, 5.0));
+        assert_eq!(read_chunk(3), (vec![], false, 5.0));
+
+        // Appending to an input invalidates the cached merge
+        new_array.push(&create_log_with_timestamp(6000.0));
+        assert_eq!(read_chunk(2), (vec![5000.0, 6000.0], false, 6.0));
+
+        // The cache stays counted across operation resets until it is cleared
+        let active_bytes = || js_sys::Map::from(get_allocation_stats()).get(&"active_bytes".into()).as_f64().unwrap();
+        reset_internal_allocation_stats();
+        assert!(active_bytes() > 0.0);
+        clear_log_cache();
+        assert_eq!(active_bytes(), 0.0);
+
+        // Nullish sides merge as empty; other non-arrays are rejected before the cache is consulted
+        let chunk = merge_and_get_chunk(JsValue::NULL, JsValue::UNDEFINED, 0, 2).unwrap();
+        assert_eq!(js_sys::Array::from(&js_sys::Reflect::get(&chunk, &"logs".into()).unwrap()).length(), 0);
+        assert_eq!(js_sys::Reflect::get(&chunk, &"total".into()).unwrap().as_f64(), Some(0.0));
+        let rejected = merge_and_get_chunk(JsValue::from(5), JsValue::NULL, 0, 2).unwrap_err();
+        assert_eq!(js_sys::Error::from(rejected).name().as_string().unwrap(), "NotAnArray");
+        clear_log_cache();
+
+        assert!(merge_and_get_chunk(existing, new, 0, 0).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_identical_time_and_sequence_keep_input_order() {
+        // Entries only distinguishable by message; existing is deliberately unsorted
+        let build = |entries: &[(f64, u32, &str)]| {
+            let logs = js_sys::Array::new();
+            for (time, sequence, message) in entries {
+                let log = create_log_with_sequence(*time, *sequence);
+                js_sys::Reflect::set(&log, &"message".into(), &(*message).into()).unwrap();
+                logs.push(&log);
+            }
+            logs
+        };
+        let existing = build(&[(2.0, 1, "e1"), (1.0, 5, "e2"), (2.0, 1, "e3"), (1.0, 5, "e4")]);
+        let new = build(&[(1.0, 5, "n1"), (2.0, 1, "n2"), (1.0, 5, "n3")]);
+        let messages = |result: JsValue| -> Vec<String> {
+            js_sys::Array::from(&result).iter()
+                .map(|log| js_sys::Reflect::get(&log, &"message".into()).unwrap().as_string().unwrap())
+                .collect()
+        };
+
+        let merged = messages(merge_insert_logs(existing.into(), new.into()).unwrap());
+        assert_eq!(merged, vec!["e2", "e4", "n1", "n3", "e1", "e3", "n2"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_estimate_memory_for_batch() {
+        let field = |estimate: JsValue, key: &str| js_sys::Map::from(estimate).get(&key.into());
+
+        let small = create_test_logs(5);
+        let large = create_test_logs(5);
+        js_sys::Reflect::set(&large.get(0), &"message".into(), &"x".repeat(100_000).into()).unwrap();
+
+        let small_estimate = estimate_memory_for_batch(small.into()).unwrap();
+        let large_estimate = estimate_memory_for_batch(large.into()).unwrap();
+        assert_eq!(field(small_estimate.clone(), "sampled").as_bool(), Some(false));
+        assert_eq!(field(small_estimate.clone(), "log_count").as_f64(), Some(5.0));
+        let small_bytes = field(small_estimate, "estimated_bytes").as_f64().unwrap();
+        let large_bytes = field(large_estimate, "estimated_bytes").as_f64().unwrap();
+        assert!(large_bytes > small_bytes + 90_000.0);
+
+        // Big batches are sampled and extrapolated to the full count
+        let estimate = estimate_memory_for_batch(create_test_logs(1000).into()).unwrap();
+        assert_eq!(field(estimate.clone(), "sampled").as_bool(), Some(true));
+        assert_eq!(field(estimate.clone(), "log_count").as_f64(), Some(1000.0));
+        assert!(field(estimate, "estimated_bytes").as_f64().unwrap() > 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_multibyte_cut_points() {
+        // The 8-byte time cut lands inside 'é', which used to panic
+        let existing = create_logs_with_timestamps(&[1.0]);
+        let new = create_logs_with_timestamps(&[2.0]);
+        js_sys::Reflect::set(&new.get(0), &"time".into(), &"2024-01-01T12:34:5é".into()).unwrap();
+        let result = js_sys::Array::from(&merge_insert_logs(existing.into(), new.into()).unwrap());
+        let time = js_sys::Reflect::get(&result.get(1), &"time".into()).unwrap().as_string().unwrap();
+        assert_eq!(time, "12:34:5");
+
+        // A 4-byte emoji straddling every possible cut is dropped whole
+        for max_len in 3..7 {
+            let logs = create_logs_with_timestamps(&[1.0]);
+            js_sys::Reflect::set(&logs.get(0), &"message".into(), &"abc😀def".into()).unwrap();
+            let result = js_sys::Array::from(&truncate_messages(logs.into(), max_len).unwrap());
+            let message = js_sys::Reflect::get(&result.get(0), &"message".into()).unwrap().as_string().unwrap();
+            assert!(message.starts_with("abc… (truncated 7 bytes)"), "cut at {} gave {:?}", max_len, message);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_stats_only() {
+        let existing = create_logs_with_timestamps(&[3.0, 1.0]);
+        let new = create_logs_with_timestamps(&[2.0]);
+        js_sys::Reflect::set(&new.get(0), &"level".into(), &"ERROR".into()).unwrap();
+
+        let summary = js_sys::Map::from(merge_insert_logs_stats_only(existing.clone().into(), new.into()).unwrap());
+        assert_eq!(summary.get(&"total".into()).as_f64(), Some(3.0));
+        assert_eq!(summary.get(&"min_unix".into()).as_f64(), Some(1000.0));
+        assert_eq!(summary.get(&"max_unix".into()).as_f64(), Some(3000.0));
+
+        let by_level = js_sys::Map::from(summary.get(&"by_level".into()));
+        assert_eq!(by_level.get(&"INFO".into()).as_f64(), Some(2.0));
+        assert_eq!(by_level.get(&"ERROR".into()).as_f64(), Some(1.0));
+
+        // An empty side summarizes the other one
+        let summary = js_sys::Map::from(merge_insert_logs_stats_only(existing.into(), JsValue::NULL).unwrap());
+        assert_eq!(summary.get(&"total".into()).as_f64(), Some(2.0));
+        assert_eq!(summary.get(&"max_unix".into()).as_f64(), Some(3000.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_error_codes() {
+        let error_name = |error: JsValue| js_sys::Error::from(error).name().as_string().unwrap();
+
+        let error = tail_logs(JsValue::from(42), 1).unwrap_err();
+        assert_eq!(error_name(error), "NotAnArray");
+
+        let error = histogram_by_time(create_test_logs(1).into(), 0).unwrap_err();
+        assert_eq!(error_name(error), "InvalidArgument");
+
+        let malformed = js_sys::Array::of1(&JsValue::from("not a log"));
+        let error = merge_insert_logs(create_test_logs(1).into(), malformed.into()).unwrap_err();
+        assert_eq!(error_name(error), "DeserializeError");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_max_message_bytes() {
+        let existing = create_logs_with_timestamps(&[1.0]);
+        let new = create_logs_with_timestamps(&[2.0]);
+        js_sys::Reflect::set(&new.get(0), &"message".into(), &"y".repeat(5000).into()).unwrap();
+
+        set_max_message_bytes(100);
+        let result = merge_insert_logs(existing.clone().into(), new.clone().into());
+        set_max_message_bytes(0);
+
+        let result = js_sys::Array::from(&result.unwrap());
+        let long = result.get(1);
+        let message = js_sys::Reflect::get(&long, &"message".into()).unwrap().as_string().unwrap();
+        assert_eq!(message, format!("{}… (truncated 4900 bytes)", "y".repeat(100)));
+        let original_len = js_sys::Reflect::get(&long, &"_original_message_len".into()).unwrap();
+        assert_eq!(original_len.as_f64(), Some(5000.0));
+        // Short messages are untouched
+        assert!(!js_sys::Reflect::has(&result.get(0), &"_original_message_len".into()).unwrap());
+
+        // With the default limit nothing is cut
+        let result = js_sys::Array::from(&merge_insert_logs(existing.into(), new.into()).unwrap());
+        let message = js_sys::Reflect::get(&result.get(1), &"message".into()).unwrap().as_string().unwrap();
+        assert_eq!(message.len(), 5000);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_normalize_single_time() {
+        assert_eq!(normalize_single_time("19:08:10"), "19:08:10");
+        assert_eq!(normalize_single_time("2024-01-01T10:20:30+02:00"), "10:20:30");
+        assert_eq!(normalize_single_time("2024-01-01T10:20:30.123Z"), "10:20:30");
+        assert_eq!(normalize_single_time("not a time"), "00:00:00");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_histogram_by_time() {
+        let logs = create_logs_with_timestamps(&[0.0, 1.0, 2.0, 3.0, 4.0]);
+        js_sys::Reflect::set(&logs.get(1), &"level".into(), &"ERROR".into()).unwrap();
+        js_sys::Reflect::set(&logs.get(3), &"level".into(), &"fatal".into()).unwrap();
+        let bin_fields = |bin: JsValue| -> Vec<f64> {
+            ["start", "end", "count", "error_count"].iter()
+                .map(|key| js_sys::Reflect::get(&bin, &(*key).into()).unwrap().as_f64().unwrap())
+                .collect()
+        };
+
+        let bins = js_sys::Array::from(&histogram_by_time(logs.clone().into(), 2).unwrap());
+        assert_eq!(bins.length(), 2);
+        assert_eq!(bin_fields(bins.get(0)), vec![0.0, 2000.0, 2.0, 1.0]);
+        // The maximum falls into the last bin
+        assert_eq!(bin_fields(bins.get(1)), vec![2000.0, 4000.0, 3.0, 1.0]);
+
+        // Identical timestamps collapse into one bin
+        let same = create_logs_with_timestamps(&[7.0, 7.0]);
+        let bins = js_sys::Array::from(&histogram_by_time(same.into(), 10).unwrap());
+        assert_eq!(bins.length(), 1);
+        assert_eq!(bin_fields(bins.get(0)), vec![7000.0, 7000.0, 2.0, 0.0]);
+
+        assert!(histogram_by_time(logs.into(), 0).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_descending_cache_order() {
+        let existing: JsValue = create_logs_with_timestamps(&[1.0, 3.0, 5.0]).into();
+        let new: JsValue = create_logs_with_timestamps(&[2.0, 4.0]).into();
+        let read_chunk = |index: usize| -> Vec<(f64, u32)> {
+            let chunk = merge_and_get_chunk(existing.clone(), new.clone(), index, 2).unwrap();
+            js_sys::Array::from(&js_sys::Reflect::get(&chunk, &"logs".into()).unwrap()).iter()
+                .map(|log| (get_unix_time_from_log(&log), get_sequence_from_log(&log)))
+                .collect()
+        };
+
+        set_cache_order("desc").unwrap();
+        let first = read_chunk(0);
+        let last = read_chunk(2);
+        set_cache_order("asc").unwrap();
+        clear_log_cache();
+
+        // Newest first, keeping the fallback sequence of the ascending position
+        assert_eq!(first, vec![(5000.0, 4), (4000.0, 3)]);
+        assert_eq!(last, vec![(1000.0, 0)]);
+        assert!(set_cache_order("sideways").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_drop_adjacent_duplicates() {
+        let logs = js_sys::Array::new();
+        let tagged = |time: f64, tag: &str| {
+            let log = create_log_with_timestamp(time);
+            js_sys::Reflect::set(&log, &"tag".into(), &tag.into()).unwrap();
+            log
+        };
+        logs.push(&tagged(1000.0, "a"));
+        logs.push(&tagged(1000.0, "a")); // Double emission, dropped
+        logs.push(&tagged(1000.0, "b")); // Differs only in an extra field, kept
+        logs.push(&tagged(1000.0, "a")); // Same as the first, but not adjacent, kept
+
+        let result = drop_adjacent_duplicates(logs.into()).unwrap();
+        let kept = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        let tags: Vec<String> = kept.iter()
+            .map(|log| js_sys::Reflect::get(&log, &"tag".into()).unwrap().as_string().unwrap())
+            .collect();
+        assert_eq!(tags, vec!["a", "b", "a"]);
+        assert_eq!(js_sys::Reflect::get(&result, &"removed".into()).unwrap().as_f64(), Some(1.0));
+
+        // Preserved complex values count: Dates all deserialize to {} but differ as emitted
+        let dated = |millis: f64| {
+            let log = create_log_with_timestamp(1000.0);
+            js_sys::Reflect::set(&log, &"at".into(), &js_sys::Date::new(&millis.into())).unwrap();
+            log
+        };
+        let logs = js_sys::Array::of3(&dated(0.0), &dated(0.0), &dated(5000.0));
+        set_preserve_complex_fields(true);
+        let result = drop_adjacent_duplicates(logs.into());
+        set_preserve_complex_fields(false);
+        let removed = js_sys::Reflect::get(&result.unwrap(), &"removed".into()).unwrap();
+        assert_eq!(removed.as_f64(), Some(1.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_top_largest_logs() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        js_sys::Reflect::set(&logs.get(1), &"payload".into(), &"x".repeat(500).into()).unwrap();
+        js_sys::Reflect::set(&logs.get(3), &"payload".into(), &"x".repeat(2000).into()).unwrap();
+        js_sys::Reflect::set(&logs.get(4), &"payload".into(), &"x".repeat(1000).into()).unwrap();
+
+        let top = js_sys::Array::from(&top_largest_logs(logs.clone().into(), 2).unwrap());
+        let indices: Vec<f64> = top.iter()
+            .map(|entry| js_sys::Reflect::get(&entry, &"index".into()).unwrap().as_f64().unwrap())
+            .collect();
+        assert_eq!(indices, vec![3.0, 4.0]);
+        let sizes: Vec<f64> = top.iter()
+            .map(|entry| js_sys::Reflect::get(&entry, &"estimated_bytes".into()).unwrap().as_f64().unwrap())
+            .collect();
+        assert!(sizes[0] > sizes[1]);
+
+        // Asking for more than there are returns every entry
+        assert_eq!(js_sys::Array::from(&top_largest_logs(logs.into(), 10).unwrap()).length(), 5);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_throwing_memory_grow_fails_gracefully() {
+        let growth_failures = || js_sys::Map::from(get_allocation_stats()).get(&"growth_failures".into()).as_f64().unwrap();
+        let failures_before = growth_failures();
+
+        // Simulate a sandbox whose grow() throws instead of returning the failure sentinel
+        let memory = wasm_bindgen::memory();
+        let throwing_grow = js_sys::Function::new_no_args("throw new RangeError('growth not allowed')");
+        js_sys::Reflect::set(&memory, &"grow".into(), &throwing_grow).unwrap();
+
+        let usage = get_memory_usage_lite();
+        let total_bytes = js_sys::Reflect::get(&usage, &"total_bytes".into()).unwrap().as_f64().unwrap() as usize;
+        let fits = ensure_sufficient_memory(total_bytes);
+        js_sys::Reflect::delete_property(&memory.into(), &"grow".into()).unwrap();
+
+        assert!(!fits);
+        assert_eq!(growth_failures(), failures_before + 1.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_case_sensitive_search_positions() {
+        assert_eq!(simd_enabled(), cfg!(target_feature = "simd128"));
+
+        // Matches at every offset around the 16-byte block boundaries, plus near misses
+        for position in 0..40 {
+            let mut message = "a".repeat(48);
+            message.replace_range(position..position + 3, "xyz");
+            let logs = create_logs_with_timestamps(&[1.0]);
+            js_sys::Reflect::set(&logs.get(0), &"message".into(), &message.into()).unwrap();
+
+            let found = filter_logs_containing(logs.clone().into(), "xyz", false).unwrap();
+            assert_eq!(js_sys::Array::from(&found).length(), 1, "missed match at {}", position);
+            let missed = filter_logs_containing(logs.into(), "xyy", false).unwrap();
+            assert_eq!(js_sys::Array::from(&missed).length(), 0, "false match at {}", position);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_tagged_sources() {
+        let existing = create_logs_with_timestamps(&[1.0, 3.0]);
+        let new = create_logs_with_timestamps(&[2.0]);
+
+        let sources = |result: JsValue| -> Vec<String> {
+            js_sys::Array::from(&result).iter()
+                .map(|log| js_sys::Reflect::get(&log, &"_source".into()).unwrap().as_string().unwrap())
+                .collect()
+        };
+        let result = merge_insert_logs_tagged(existing.into(), new.clone().into(), "backend", "worker").unwrap();
+        assert_eq!(sources(result), vec!["backend", "worker", "backend"]);
+
+        // Entries are tagged even when the other side is empty
+        let result = merge_insert_logs_tagged(JsValue::NULL, new.into(), "backend", "worker").unwrap();
+        assert_eq!(sources(result), vec!["worker"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_windowed_average_allocation() {
+        // One merge with a huge entry followed by several ordinary ones
+        let average_after_outlier = || {
+            reset_all_allocation_stats();
+            let huge = create_logs_with_timestamps(&[1.0]);
+            js_sys::Reflect::set(&huge.get(0), &"message".into(), &"z".repeat(1_000_000).into()).unwrap();
+            merge_insert_logs(huge.into(), create_logs_with_timestamps(&[2.0]).into()).unwrap();
+            for _ in 0..5 {
+                merge_insert_logs(create_test_logs(2).into(), create_test_logs(2).into()).unwrap();
+            }
+            js_sys::Map::from(get_allocation_stats()).get(&"average_allocation".into()).as_f64().unwrap()
+        };
+
+        let cumulative = average_after_outlier();
+        set_average_window(2);
+        let windowed = average_after_outlier();
+        set_average_window(0);
+        reset_all_allocation_stats();
+
+        assert!(windowed * 10.0 < cumulative, "windowed {} vs cumulative {}", windowed, cumulative);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_peek_bounds() {
+        let logs = js_sys::Array::new();
+        logs.push(&create_log_with_sequence(1.0, 4));
+        logs.push(&create_log_with_sequence(2.0, 5));
+        logs.push(&create_log_with_timestamp(3000.0));
+        let field = |bounds: &JsValue, key: &str| js_sys::Reflect::get(bounds, &key.into()).unwrap();
+
+        let bounds = peek_bounds(&logs.into());
+        assert_eq!(field(&bounds, "first_unix").as_f64(), Some(1000.0));
+        assert_eq!(field(&bounds, "first_seq").as_f64(), Some(4.0));
+        assert_eq!(field(&bounds, "last_unix").as_f64(), Some(3000.0));
+        assert!(field(&bounds, "last_seq").is_null());
+        assert_eq!(field(&bounds, "length").as_f64(), Some(3.0));
+
+        let bounds = peek_bounds(&js_sys::Array::new().into());
+        assert!(field(&bounds, "first_unix").is_null());
+        assert!(field(&bounds, "last_seq").is_null());
+        assert_eq!(field(&bounds, "length").as_f64(), Some(0.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_height_clamp() {
+        let logs = js_sys::Array::new();
+        logs.push(&create_log_with_sequence(1.0, 1));
+        logs.push(&create_log_with_sequence(2.0, 2));
+        let heights = js_sys::Object::new();
+        js_sys::Reflect::set(&heights, &JsValue::from(1), &JsValue::from(400.0)).unwrap();
+
+        // Row 2 has no measurement and falls back to the average, capped at 100px by default
+        let total = compute_total_height(logs.clone().into(), heights.clone().into(), 250.0, 0.0).unwrap();
+        assert_eq!(total, 500.0);
+
+        set_height_clamp(20.0, 300.0).unwrap();
+        let total = compute_total_height(logs.into(), heights.into(), 250.0, 0.0);
+        set_height_clamp(20.0, 100.0).unwrap();
+        assert_eq!(total.unwrap(), 650.0);
+
+        assert!(set_height_clamp(50.0, 10.0).is_err());
+        assert!(set_height_clamp(f64::NAN, 10.0).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_binary_round_trip() {
+        let logs = js_sys::Array::new();
+        logs.push(&create_log_with_sequence(1.0, 1));
+        let tagged = create_log_with_sequence(2.0, 2);
+        js_sys::Reflect::set(&tagged, &"component".into(), &"ffmpeg".into()).unwrap();
+        js_sys::Reflect::set(&tagged, &"_visible".into(), &false.into()).unwrap();
+        js_sys::Reflect::set(&tagged, &"_height".into(), &42.5.into()).unwrap();
+        js_sys::Reflect::set(&tagged, &"message".into(), &"wörk – ünïcode".into()).unwrap();
+        logs.push(&tagged);
+
+        let bytes = serialize_logs_binary(logs.into()).unwrap();
+        assert_eq!(&bytes[..3], b"LKB");
+
+        let restored = js_sys::Array::from(&deserialize_logs_binary(&bytes).unwrap());
+        assert_eq!(restored.length(), 2);
+        assert_eq!(get_unix_time_from_log(&restored.get(0)), 1000.0);
+        assert_eq!(get_sequence_from_log(&restored.get(1)), 2);
+
+        let second = restored.get(1);
+        let get = |key: &str| js_sys::Reflect::get(&second, &key.into()).unwrap();
+        assert_eq!(get("level").as_string().unwrap(), "INFO");
+        assert_eq!(get("message").as_string().unwrap(), "wörk – ünïcode");
+        assert_eq!(get("component").as_string().unwrap(), "ffmpeg");
+        assert_eq!(get("_visible").as_bool(), Some(false));
+        assert_eq!(get("_height").as_f64(), Some(42.5));
+        assert!(get("behavior").is_undefined() || get("behavior").is_null());
+
+        // Empty batches round-trip too
+        let empty = serialize_logs_binary(js_sys::Array::new().into()).unwrap();
+        assert_eq!(js_sys::Array::from(&deserialize_logs_binary(&empty).unwrap()).length(), 0);
+
+        // Wrong header, unknown version and truncated data are rejected
+        assert!(deserialize_logs_binary(b"{\"level\":\"INFO\"}").is_err());
+        let mut future = bytes.clone();
+        future[3] = 99;
+        assert!(deserialize_logs_binary(&future).is_err());
+        assert!(deserialize_logs_binary(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_detect_clock_regressions() {
+        let logs = js_sys::Array::new();
+        logs.push(&create_log_with_timestamp(1000.0));
+        logs.push(&create_log_with_timestamp(5000.0));
+        let untimed = create_log_with_timestamp(0.0);
+        js_sys::Reflect::delete_property(&untimed, &"_unix_time".into()).unwrap();
+        logs.push(&untimed);
+        logs.push(&create_log_with_timestamp(3500.0));
+        logs.push(&create_log_with_timestamp(3500.0));
+        logs.push(&create_log_with_timestamp(6000.0));
+
+        let regressions = js_sys::Array::from(&detect_clock_regressions(logs.into()).unwrap());
+        assert_eq!(regressions.length(), 1);
+
+        let regression = regressions.get(0);
+        let get = |key: &str| js_sys::Reflect::get(&regression, &key.into()).unwrap().as_f64().unwrap();
+        assert_eq!(get("index"), 3.0);
+        assert_eq!(get("previous_index"), 1.0);
+        assert_eq!(get("backward_by"), 1500.0);
+
+        let sorted = create_sorted_logs(1, 5);
+        assert_eq!(js_sys::Array::from(&detect_clock_regressions(sorted.into()).unwrap()).length(), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_append_only_newer() {
+        let existing = js_sys::Array::new();
+        existing.push(&create_log_with_sequence(10.0, 1));
+        existing.push(&create_log_with_sequence(20.0, 2));
+        let new = js_sys::Array::new();
+        new.push(&create_log_with_sequence(30.0, 4));
+        new.push(&create_log_with_sequence(15.0, 3)); // late arrival
+        new.push(&create_log_with_sequence(20.0, 2)); // same as the tail
+        new.push(&create_log_with_sequence(20.0, 5)); // same time, later sequence
+
+        let result = append_only_newer(existing.clone().into(), new.clone().into()).unwrap();
+        let logs = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        let dropped = js_sys::Reflect::get(&result, &"dropped".into()).unwrap().as_f64().unwrap();
+        assert_eq!(dropped, 2.0);
+        let sequences: Vec<u32> = logs.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![1, 2, 5, 4]);
+
+        // With no existing logs there is no tail, so the whole batch is appended in order
+        let result = append_only_newer(JsValue::NULL, new.clone().into()).unwrap();
+        let logs = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        assert_eq!(js_sys::Reflect::get(&result, &"dropped".into()).unwrap().as_f64(), Some(0.0));
+        let sequences: Vec<u32> = logs.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![3, 2, 5, 4]);
+
+        // The full merge inserts the late entry mid-stream instead
+        let merged = js_sys::Array::from(&merge_insert_logs(existing.into(), new.into()).unwrap());
+        assert_eq!(merged.length(), 6);
+        assert_eq!(get_sequence_from_log(&merged.get(1)), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_compact_output() {
+        let make_logs = || {
+            let logs = js_sys::Array::new();
+            for i in 0..50u32 {
+                let log = create_log_with_sequence(i as f64, i);
+                js_sys::Reflect::set(&log, &"level".into(), &"info".into()).unwrap();
+                js_sys::Reflect::set(&log, &"message".into(), &"".into()).unwrap();
+                logs.push(&log);
+            }
+            let warning = create_log_with_sequence(100.0, 7);
+            js_sys::Reflect::set(&warning, &"level".into(), &"WARN".into()).unwrap();
+            logs.push(&warning);
+            logs
+        };
+        let stringify = |value: &JsValue| js_sys::JSON::stringify(value).unwrap().length();
+
+        let full = merge_insert_logs(make_logs().into(), js_sys::Array::of1(&create_log_with_sequence(200.0, 51)).into()).unwrap();
+        set_compact_output(true);
+        let compact = merge_insert_logs(make_logs().into(), js_sys::Array::of1(&create_log_with_sequence(200.0, 51)).into());
+        set_compact_output(false);
+        let compact = compact.unwrap();
+
+        let full_bytes = stringify(&full);
+        let compact_bytes = stringify(&compact);
+        assert!(compact_bytes * 10 < full_bytes * 8, "compact {} vs full {}", compact_bytes, full_bytes);
+
+        let compact = js_sys::Array::from(&compact);
+        let first = compact.get(0);
+        let has = |log: &JsValue, key: &str| js_sys::Reflect::has(log, &key.into()).unwrap();
+        assert!(!has(&first, "level"));
+        assert!(!has(&first, "message"));
+        assert!(!has(&first, "_sequence"));
+        assert!(has(&first, "_unix_time"));
+        assert!(has(&first, "time"));
+
+        // Non-default values are still written
+        let warning = compact.get(50);
+        assert_eq!(js_sys::Reflect::get(&warning, &"level".into()).unwrap().as_string().unwrap(), "WARN");
+        assert_eq!(get_sequence_from_log(&warning), 7);
+        assert_eq!(js_sys::Reflect::get(&compact.get(51), &"message".into()).unwrap().as_string().unwrap(), "Test message");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_logs_with_behavior() {
+        let logs = create_test_logs(5);
+        for (index, behavior) in [(1, "progress"), (2, "error"), (4, "progress")] {
+            js_sys::Reflect::set(&logs.get(index), &"behavior".into(), &behavior.into()).unwrap();
+        }
+
+        let indices = find_logs_with_behavior(logs.clone().into(), "progress").unwrap();
+        assert_eq!(indices.to_vec(), vec![1, 4]);
+        assert_eq!(find_logs_with_behavior(logs.clone().into(), "missing").unwrap().length(), 0);
+
+        let behaviors: Vec<String> = js_sys::Array::from(&distinct_behaviors(logs.into()).unwrap())
+            .iter()
+            .map(|value| value.as_string().unwrap())
+            .collect();
+        assert_eq!(behaviors, vec!["progress", "error"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_reject_skew() {
+        let now = js_sys::Date::now() / 1000.0;
+        let existing = js_sys::Array::of1(&create_log_with_timestamp(now - 10.0));
+        let new = js_sys::Array::new();
+        new.push(&create_log_with_timestamp(now - 3600.0)); // past is always fine
+        new.push(&create_log_with_timestamp(now + 30.0));
+        let far_future = create_log_with_timestamp(now + 86400.0);
+        js_sys::Reflect::set(&far_future, &"message".into(), &"desynced".into()).unwrap();
+        new.push(&far_future);
+
+        let result = merge_insert_logs_reject_skew(existing.clone().into(), new.clone().into(), 60.0).unwrap();
+        let logs = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        let rejected = js_sys::Array::from(&js_sys::Reflect::get(&result, &"rejected".into()).unwrap());
+
+        assert_eq!(logs.length(), 3);
+        assert_eq!(get_unix_time_from_log(&logs.get(0)), now - 3600.0);
+        assert_eq!(get_unix_time_from_log(&logs.get(2)), now + 30.0);
+        assert_eq!(rejected.length(), 1);
+        assert_eq!(js_sys::Reflect::get(&rejected.get(0), &"message".into()).unwrap().as_string().unwrap(), "desynced");
+
+        // Nothing new: existing comes back as given
+        let result = merge_insert_logs_reject_skew(existing.clone().into(), JsValue::NULL, 60.0).unwrap();
+        assert!(js_sys::Object::is(&js_sys::Reflect::get(&result, &"logs".into()).unwrap(), &existing));
+        assert_eq!(js_sys::Array::from(&js_sys::Reflect::get(&result, &"rejected".into()).unwrap()).length(), 0);
+        // No existing entries: new ones are still screened
+        let result = merge_insert_logs_reject_skew(JsValue::NULL, new.into(), 60.0).unwrap();
+        assert_eq!(js_sys::Array::from(&js_sys::Reflect::get(&result, &"rejected".into()).unwrap()).length(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_scroll_position_for_index() {
+        let logs = js_sys::Array::new();
+        for sequence in 1..=5u32 {
+            logs.push(&create_log_with_sequence(sequence as f64, sequence));
+        }
+        let positions = js_sys::Object::new();
+        js_sys::Reflect::set(&positions, &JsValue::from(3), &JsValue::from(500.0)).unwrap();
+        let heights = js_sys::Object::new();
+        js_sys::Reflect::set(&heights, &JsValue::from(1), &JsValue::from(40.0)).unwrap();
+        js_sys::Reflect::set(&heights, &JsValue::from(3), &JsValue::from(70.0)).unwrap();
+
+        let scroll = |index: usize| {
+            scroll_position_for_index(logs.clone().into(), positions.clone().into(), heights.clone().into(), index, 30.0, 2.0).unwrap()
+        };
+        assert_eq!(scroll(0), 0.0);
+        // Sequence 1 is measured at 40, plus the buffer
+        assert_eq!(scroll(1), 42.0);
+        // Sequence 3 has a known position, which restarts the accumulation
+        assert_eq!(scroll(2), 500.0);
+        assert_eq!(scroll(3), 572.0);
+        // Sequence 4 is unmeasured and uses the average of 30
+        assert_eq!(scroll(4), 604.0);
+        // Out-of-range indices clamp to the last row
+        assert_eq!(scroll(99), 604.0);
+        assert_eq!(scroll_position_for_index(js_sys::Array::new().into(), JsValue::NULL, JsValue::NULL, 3, 30.0, 0.0).unwrap(), 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_dedup_last_wins() {
+        let state_log = |time: f64, sequence: u32, task: &str, stage: &str, status: &str| {
+            let log = create_log_with_sequence(time, sequence);
+            js_sys::Reflect::set(&log, &"task".into(), &task.into()).unwrap();
+            js_sys::Reflect::set(&log, &"stage".into(), &stage.into()).unwrap();
+            js_sys::Reflect::set(&log, &"message".into(), &status.into()).unwrap();
+            log
+        };
+        let existing = js_sys::Array::new();
+        existing.push(&state_log(1.0, 1, "a", "encode", "started"));
+        existing.push(&state_log(5.0, 5, "a", "encode", "finished"));
+        existing.push(&state_log(2.0, 2, "b", "encode", "started"));
+        let existing_copy = existing.slice(0, existing.length());
+        let new = js_sys::Array::new();
+        new.push(&state_log(3.0, 3, "a", "encode", "stale")); // older than the existing update
+        new.push(&state_log(4.0, 4, "b", "encode", "finished"));
+        new.push(&state_log(4.0, 6, "a", "mux", "started")); // different stage, different key
+        new.push(&create_log_with_sequence(0.5, 0)); // no key fields, always kept
+
+        let keys = js_sys::Array::of2(&"task".into(), &"stage".into());
+        let merged = js_sys::Array::from(&merge_dedup_last_wins(existing.into(), new.into(), keys.into()).unwrap());
+
+        let messages: Vec<String> = merged.iter()
+            .map(|log| js_sys::Reflect::get(&log, &"message".into()).unwrap().as_string().unwrap())
+            .collect();
+        assert_eq!(messages, vec!["Test message", "finished", "started", "finished"]);
+        let sequences: Vec<u32> = merged.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![0, 4, 6, 5]);
+
+        // A null side counts as empty, so the other side is still deduplicated
+        let keys = js_sys::Array::of2(&"task".into(), &"stage".into());
+        let merged = js_sys::Array::from(&merge_dedup_last_wins(JsValue::NULL, existing_copy.into(), keys.into()).unwrap());
+        assert_eq!(merged.length(), 2);
+
+        let invalid = merge_dedup_last_wins(js_sys::Array::new().into(), js_sys::Array::new().into(), js_sys::Array::new().into());
+        assert!(invalid.is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_downsample_logs() {
+        let logs = js_sys::Array::new();
+        // A storm of 10 entries within second 100, with one warning and two errors
+        for i in 0..10u32 {
+            let log = create_log_with_timestamp(100.0 + i as f64 * 0.05);
+            js_sys::Reflect::set(&log, &"_sequence".into(), &i.into()).unwrap();
+            let level = match i {
+                3 => "WARN",
+                5 | 6 => "error",
+                _ => "info",
+            };
+            js_sys::Reflect::set(&log, &"level".into(), &level.into()).unwrap();
+            logs.push(&log);
+        }
+        // Quiet seconds are untouched
+        for i in 10..12u32 {
+            let log = create_log_with_timestamp(101.0 + (i - 10) as f64);
+            js_sys::Reflect::set(&log, &"_sequence".into(), &i.into()).unwrap();
+            logs.push(&log);
+        }
+
+        let result = downsample_logs(logs.clone().into(), 4).unwrap();
+        let kept = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        let dropped = js_sys::Reflect::get(&result, &"dropped".into()).unwrap().as_f64().unwrap();
+        let sequences: Vec<u32> = kept.iter().map(|log| get_sequence_from_log(&log)).collect();
+        // Both errors, then first and last of the bucket fill the cap
+        assert_eq!(sequences, vec![0, 5, 6, 9, 10, 11]);
+        assert_eq!(dropped, 6.0);
+
+        // Errors survive even when they alone exceed the cap
+        let result = downsample_logs(logs.clone().into(), 1).unwrap();
+        let kept = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        let sequences: Vec<u32> = kept.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![5, 6, 10, 11]);
+
+        // With room to spare, the warning is preferred over other info entries
+        let result = downsample_logs(logs.clone().into(), 5).unwrap();
+        let kept = js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap());
+        let sequences: Vec<u32> = kept.iter().map(|log| get_sequence_from_log(&log)).collect();
+        assert_eq!(sequences, vec![0, 3, 5, 6, 9, 10, 11]);
+
+        assert!(downsample_logs(logs.into(), 0).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_reserved_matches_merge() {
+        let existing = create_sorted_logs(1, 50);
+        let new = create_sorted_logs(25, 50);
+        let expected = js_sys::JSON::stringify(&merge_insert_logs(existing.clone().into(), new.clone().into()).unwrap()).unwrap();
+
+        // Exact, too small, too large and absurd hints all give the same array
+        for hint in [100, 0, 10, 250, usize::MAX] {
+            let merged = merge_insert_logs_reserved(existing.clone().into(), new.clone().into(), hint).unwrap();
+            assert_eq!(js_sys::Array::from(&merged).length(), 100, "hint {}", hint);
+            assert_eq!(js_sys::JSON::stringify(&merged).unwrap(), expected, "hint {}", hint);
+        }
+
+        // Like merge_insert_logs, an empty side hands back the other input
+        let unchanged = merge_insert_logs_reserved(existing.clone().into(), JsValue::NULL, 100).unwrap();
+        assert!(js_sys::Object::is(&unchanged, &existing));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_classify_behavior() {
+        assert_eq!(classify_behavior("progress"), "progress");
+        assert_eq!(classify_behavior(" Progress-Bar "), "progress");
+        assert_eq!(classify_behavior("SPINNER"), "spinner");
+        assert_eq!(classify_behavior("status"), "status");
+        assert_eq!(classify_behavior("confetti"), "custom");
+        assert_eq!(classify_behavior(""), "custom");
+
+        let logs = create_test_logs(2);
+        js_sys::Reflect::set(&logs.get(0), &"behavior".into(), &"Spinner".into()).unwrap();
+        let kind_of = |merged: &JsValue, index: u32| {
+            js_sys::Reflect::get(&js_sys::Array::from(merged).get(index), &"_behavior_kind".into()).unwrap()
+        };
+
+        let plain = merge_insert_logs(logs.clone().into(), create_sorted_logs(5000, 1).into()).unwrap();
+        assert!(kind_of(&plain, 0).is_undefined());
+
+        set_emit_behavior_kind(true);
+        let tagged = merge_insert_logs(logs.into(), create_sorted_logs(5000, 1).into());
+        set_emit_behavior_kind(false);
+        let tagged = tagged.unwrap();
+        assert_eq!(kind_of(&tagged, 0).as_string().unwrap(), "spinner");
+        // Entries without a behavior get no kind
+        assert!(kind_of(&tagged, 1).is_undefined());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_merge_invariants() {
+        let merged = merge_insert_logs(create_test_logs(20).into(), create_sorted_logs(1005, 10).into()).unwrap();
+        let report = verify_merge_invariants(&merged).unwrap();
+        assert_eq!(js_sys::Reflect::get(&report, &"ok".into()).unwrap().as_bool(), Some(true));
+
+        let broken = js_sys::Array::from(&merged);
+        broken.set(3, create_log_with_timestamp(1.0).into());
+        js_sys::Reflect::set(&broken.get(5), &"message".into(), &JsValue::NULL).unwrap();
+        js_sys::Reflect::set(&broken.get(7), &"_unix_time".into(), &JsValue::from(f64::NAN)).unwrap();
+
+        let report = verify_merge_invariants(&broken).unwrap();
+        assert_eq!(js_sys::Reflect::get(&report, &"ok".into()).unwrap().as_bool(), Some(false));
+        let violations: Vec<(u32, String)> = js_sys::Array::from(&js_sys::Reflect::get(&report, &"violations".into()).unwrap())
+            .iter()
+            .map(|violation| (
+                js_sys::Reflect::get(&violation, &"index".into()).unwrap().as_f64().unwrap() as u32,
+                js_sys::Reflect::get(&violation, &"reason".into()).unwrap().as_string().unwrap(),
+            ))
+            .collect();
+        assert_eq!(violations, vec![
+            (3, "out of order".to_string()),
+            (5, "missing message".to_string()),
+            (7, "NaN _unix_time".to_string()),
+        ]);
+
+        assert!(verify_merge_invariants(&JsValue::from(5)).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verify_merge_invariants_compact_output() {
+        let make_logs = |entries: &[(f64, u32)]| {
+            let logs = js_sys::Array::new();
+            for &(unix_time, sequence) in entries {
+                let log = create_log_with_sequence(unix_time, sequence);
+                js_sys::Reflect::set(&log, &"level".into(), &"info".into()).unwrap();
+                js_sys::Reflect::set(&log, &"message".into(), &"".into()).unwrap();
+                logs.push(&log);
+            }
+            logs
+        };
+
+        // The tie at 5.0 has an explicit sequence followed by a positional one
+        set_compact_output(true);
+        let merged = merge_insert_logs(make_logs(&[(1.0, 5), (2.0, 6)]).into(), make_logs(&[(5.0, 1), (5.0, 3)]).into());
+        set_compact_output(false);
+        let merged = js_sys::Array::from(&merged.unwrap());
+
+        let has = |log: &JsValue, key: &str| js_sys::Reflect::has(log, &key.into()).unwrap();
+        assert!(!has(&merged.get(3), "level"));
+        assert!(!has(&merged.get(3), "message"));
+        assert!(!has(&merged.get(3), "_sequence"));
+
+        let report = verify_merge_invariants(&merged).unwrap();
+        assert_eq!(js_sys::Reflect::get(&report, &"ok".into()).unwrap().as_bool(), Some(true));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_growth_callback_can_reenter_tracker() {
+        // The callback reads the tracker stats, which must not conflict with the growth path
+        let seen = js_sys::Array::new();
+        let callback = js_sys::Function::new_with_args(
+            "stats_fn, seen, event",
+            "seen.push({ event, stats: stats_fn() });",
+        );
+        let stats_fn = wasm_bindgen::closure::Closure::<dyn Fn() -> JsValue>::new(get_allocation_stats);
+        let callback = callback.bind2(&JsValue::NULL, stats_fn.as_ref(), &seen);
+        set_growth_callback(callback.into());
+
+        // Pretend growth succeeds without actually growing the test's memory
+        let memory = wasm_bindgen::memory();
+        let fake_grow = js_sys::Function::new_no_args("return 0;");
+        js_sys::Reflect::set(&memory, &"grow".into(), &fake_grow).unwrap();
+
+        let usage = get_memory_usage_lite();
+        let total_bytes = js_sys::Reflect::get(&usage, &"total_bytes".into()).unwrap().as_f64().unwrap() as usize;
+        let grown = ensure_sufficient_memory(total_bytes);
+        js_sys::Reflect::delete_property(&memory.into(), &"grow".into()).unwrap();
+        set_growth_callback(JsValue::NULL);
+
+        assert!(grown);
+        assert_eq!(seen.length(), 1);
+        let call = seen.get(0);
+        let event = js_sys::Reflect::get(&call, &"event".into()).unwrap();
+        assert!(js_sys::Reflect::get(&event, &"pages".into()).unwrap().as_f64().unwrap() > 0.0);
+        // The stats read inside the callback already include this growth
+        let stats = js_sys::Map::from(js_sys::Reflect::get(&call, &"stats".into()).unwrap());
+        assert!(stats.get(&"growth_events".into()).as_f64().unwrap() >= 1.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_estimate_remaining_log_capacity() {
+        // Large messages raise the per-log average, so fewer logs fit
+        let before = estimate_remaining_log_capacity();
+        let logs = create_test_logs(20);
+        for log in logs.iter() {
+            js_sys::Reflect::set(&log, &"message".into(), &"x".repeat(50_000).into()).unwrap();
+        }
+        find_sequence_gaps(logs.into()).unwrap();
+        let after = estimate_remaining_log_capacity();
+        assert!(after < before, "capacity {} should drop below {}", after, before);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sort_logs_by_field() {
+        let logs = js_sys::Array::new();
+        let offsets = [JsValue::from(300), JsValue::from("n/a"), JsValue::from(100), JsValue::UNDEFINED, JsValue::from(200), JsValue::from(100)];
+        for (sequence, offset) in offsets.iter().enumerate() {
+            let log = create_log_with_sequence(sequence as f64, sequence as u32);
+            if !offset.is_undefined() {
+                js_sys::Reflect::set(&log, &"offset".into(), offset).unwrap();
+            }
+            logs.push(&log);
+        }
+        let order = |ascending: bool| -> Vec<u32> {
+            let sorted = js_sys::Array::from(&sort_logs_by_field(logs.clone().into(), "offset", ascending).unwrap());
+            sorted.iter().map(|log| get_sequence_from_log(&log)).collect()
+        };
+
+        // Ties keep input order, and non-numeric or missing offsets go last either way
+        assert_eq!(order(true), vec![2, 5, 4, 0, 1, 3]);
+        assert_eq!(order(false), vec![0, 4, 2, 5, 1, 3]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_and_report_inserts() {
+        let existing = create_logs_with_timestamps(&[10.0, 20.0, 30.0, 40.0]);
+        let new = create_logs_with_timestamps(&[45.0, 5.0, 25.0]);
+
+        let report = merge_and_report_inserts(existing.clone().into(), new.clone().into()).unwrap();
+        let merged_length = js_sys::Reflect::get(&report, &"merged_length".into()).unwrap().as_f64().unwrap();
+        let inserted: js_sys::Uint32Array = js_sys::Reflect::get(&report, &"inserted_indices".into()).unwrap().into();
+        assert_eq!(merged_length, 7.0);
+        assert_eq!(inserted.to_vec(), vec![0, 3, 6]);
+
+        // The merged array is served from the cache by the paging API
+        let chunk = merge_and_get_chunk(existing.into(), new.clone().into(), 0, 10).unwrap();
+        let logs = js_sys::Array::from(&js_sys::Reflect::get(&chunk, &"logs".into()).unwrap());
+        clear_log_cache();
+        let times: Vec<f64> = logs.iter().map(|log| get_unix_time_from_log(&log)).collect();
+        assert_eq!(times.len(), 7);
+        for index in inserted.to_vec() {
+            assert!([5.0, 25.0, 45.0].contains(&(times[index as usize] / 1000.0)));
+        }
+
+        // A null side merges as empty, and paging with the same null reuses the cache
+        let report = merge_and_report_inserts(JsValue::NULL, new.clone().into()).unwrap();
+        let inserted: js_sys::Uint32Array = js_sys::Reflect::get(&report, &"inserted_indices".into()).unwrap().into();
+        assert_eq!(inserted.to_vec(), vec![0, 1, 2]);
+        js_sys::Reflect::set(&new.get(0), &"message".into(), &"edited in place".into()).unwrap();
+        let chunk = merge_and_get_chunk(JsValue::NULL, new.into(), 0, 10).unwrap();
+        let logs = js_sys::Array::from(&js_sys::Reflect::get(&chunk, &"logs".into()).unwrap());
+        clear_log_cache();
+        assert_eq!(logs.length(), 3);
+        assert!(logs.iter().all(|log| js_sys::Reflect::get(&log, &"message".into()).unwrap().as_string().unwrap() == "Test message"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_non_array_inputs() {
+        let error_name = |result: Result<JsValue, JsValue>| js_sys::Error::from(result.unwrap_err()).name().as_string().unwrap();
+        let plain_object: JsValue = js_sys::Object::new().into();
+
+        for input in [JsValue::NULL, JsValue::UNDEFINED, plain_object.clone(), JsValue::from("logs")] {
+            let positions = js_sys::Object::new();
+            assert_eq!(error_name(scroll_position_for_index(input.clone(), positions.clone().into(), positions.into(), 0, 30.0, 0.0).map(JsValue::from)), "NotAnArray");
+            assert_eq!(error_name(compute_total_height(input.clone(), JsValue::NULL, 30.0, 0.0).map(JsValue::from)), "NotAnArray");
+            assert_eq!(error_name(compute_level_time_ranges(input.clone())), "NotAnArray");
+            assert_eq!(error_name(tail_logs(input.clone(), 5)), "NotAnArray");
+            assert_eq!(error_name(estimate_memory_for_batch(input.clone())), "NotAnArray");
+            assert_eq!(error_name(verify_merge_invariants(&input)), "NotAnArray");
+            // An empty needle matches everything, but the input must still be an array
+            assert_eq!(error_name(filter_logs_containing(input.clone(), "", false)), "NotAnArray");
+        }
+
+        // merge_insert_logs treats a missing side as empty, but still rejects other values
+        let logs = create_test_logs(3);
+        let merged = merge_insert_logs(logs.clone().into(), JsValue::NULL).unwrap();
+        assert_eq!(js_sys::Array::from(&merged).length(), 3);
+        let merged = merge_insert_logs(JsValue::UNDEFINED, logs.clone().into()).unwrap();
+        assert_eq!(js_sys::Array::from(&merged).length(), 3);
+        let merged = merge_insert_logs(JsValue::NULL, JsValue::NULL).unwrap();
+        assert_eq!(js_sys::Array::from(&merged).length(), 0);
+        assert_eq!(error_name(merge_insert_logs(logs.into(), plain_object)), "NotAnArray");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_ui_metadata_survives_merge() {
+        let existing = create_logs_with_timestamps(&[1.0, 3.0, 5.0]);
+        for (index, log) in existing.iter().enumerate() {
+            js_sys::Reflect::set(&log, &"_height".into(), &JsValue::from(30.0 + index as f64)).unwrap();
+            js_sys::Reflect::set(&log, &"_visible".into(), &JsValue::from(index != 1)).unwrap();
+            js_sys::Reflect::set(&log, &"_original_time".into(), &format!("orig-{}", index).into()).unwrap();
+        }
+        let new = create_logs_with_timestamps(&[2.0, 4.0]);
+
+        let merged = js_sys::Array::from(&merge_insert_logs(existing.into(), new.into()).unwrap());
+        assert_eq!(merged.length(), 5);
+
+        for (index, log) in merged.iter().enumerate() {
+            let get = |key: &str| js_sys::Reflect::get(&log, &key.into()).unwrap();
+            if index % 2 == 0 {
+                let original = index / 2;
+                assert_eq!(get("_height").as_f64(), Some(30.0 + original as f64));
+                assert_eq!(get("_visible").as_bool(), Some(original != 1));
+                assert_eq!(get("_original_time").as_string(), Some(format!("orig-{}", original)));
+            } else {
+                // Entries that never had the fields don't gain them
+                assert!(!js_sys::Reflect::has(&log, &"_height".into()).unwrap());
+                assert!(!js_sys::Reflect::has(&log, &"_visible".into()).unwrap());
+                assert!(!js_sys::Reflect::has(&log, &"_original_time".into()).unwrap());
+            }
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_count_logs_containing() {
+        let logs = create_test_logs(4);
+        for (index, message) in ["Disk FULL", "all good", "disk full again", "no message here"].iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"message".into(), &(*message).into()).unwrap();
+        }
+
+        for (needle, case_insensitive) in [("disk full", false), ("disk full", true), ("good", false), ("absent", true)] {
+            let filtered = filter_logs_containing(logs.clone().into(), needle, case_insensitive).unwrap();
+            let count = count_logs_containing(logs.clone().into(), needle, case_insensitive).unwrap();
+            assert_eq!(count, js_sys::Array::from(&filtered).length() as usize, "needle {:?}", needle);
+        }
+        assert_eq!(count_logs_containing(logs.clone().into(), "disk full", true).unwrap(), 2);
+        assert_eq!(count_logs_containing(logs.into(), "", false).unwrap(), 4);
+        assert!(count_logs_containing(JsValue::NULL, "", false).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_memory_soft_cap() {
+        let usage = get_memory_usage_lite();
+        let total_bytes = js_sys::Reflect::get(&usage, &"total_bytes".into()).unwrap().as_f64().unwrap() as usize;
+
+        // Record grow calls instead of growing
+        let grows = js_sys::Array::new();
+        let memory = wasm_bindgen::memory();
+        let fake_grow = js_sys::Function::new_with_args("grows, pages", "grows.push(pages); return 0;").bind1(&JsValue::NULL, &grows);
+        js_sys::Reflect::set(&memory, &"grow".into(), &fake_grow).unwrap();
+
+        set_memory_soft_cap_bytes(total_bytes);
+        let reported = js_sys::Map::from(get_memory_usage()).get(&"soft_cap_bytes".into()).as_f64();
+        let refused = ensure_sufficient_memory(total_bytes);
+        // Room for the need but not the 1MB-per-grow minimum: the growth is trimmed to the cap
+        set_min_growth_pages(60_000);
+        set_memory_soft_cap_bytes(total_bytes * 3);
+        let trimmed = ensure_sufficient_memory(total_bytes);
+        set_min_growth_pages(16);
+        set_memory_soft_cap_bytes(0);
+        js_sys::Reflect::delete_property(&memory.into(), &"grow".into()).unwrap();
+
+        assert_eq!(reported, Some(total_bytes as f64));
+        assert!(!refused);
+        assert!(trimmed);
+        assert_eq!(grows.length(), 1);
+        assert_eq!(grows.get(0).as_f64(), Some((total_bytes * 3 / 65536 - total_bytes / 65536) as f64));
+        assert_eq!(js_sys::Map::from(get_memory_usage()).get(&"soft_cap_bytes".into()).as_f64(), Some(0.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_reverse_logs() {
+        let logs = create_sorted_logs(1, 4);
+        let reversed = js_sys::Array::from(&reverse_logs(logs.clone().into()).unwrap());
+
+        assert_eq!(reversed.length(), 4);
+        for index in 0..4 {
+            // The very same entry objects, in the opposite order
+            assert!(js_sys::Object::is(&reversed.get(index), &logs.get(3 - index)));
+        }
+        // The input keeps its order
+        assert_eq!(get_unix_time_from_log(&logs.get(0)), 1000.0);
+
+        assert_eq!(js_sys::Array::from(&reverse_logs(js_sys::Array::new().into()).unwrap()).length(), 0);
+        assert!(reverse_logs(JsValue::NULL).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sanitize_messages() {
+        let messages = [
+            "\x1b[1;31mERROR\x1b[0m: disk full\r\n",
+            "  progress 50%\r",
+            "already clean",
+            "\x1b[38;5;208morange\x1b[m and \x1b]not csi",
+        ];
+        let logs = create_test_logs(messages.len() as u32);
+        for (index, message) in messages.iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"message".into(), &(*message).into()).unwrap();
+        }
+        let field = |logs: &js_sys::Array, index: u32, key: &str| js_sys::Reflect::get(&logs.get(index), &key.into()).unwrap();
+
+        let sanitized = js_sys::Array::from(&sanitize_messages(logs.clone().into(), true).unwrap());
+        let expected = ["ERROR: disk full", "progress 50%", "already clean", "orange and \x1b]not csi"];
+        for (index, expected) in expected.iter().enumerate() {
+            assert_eq!(field(&sanitized, index as u32, "message").as_string().unwrap(), *expected);
+        }
+        // The original is only kept for messages that changed
+        assert_eq!(field(&sanitized, 0, "_raw_message").as_string().unwrap(), messages[0]);
+        assert!(field(&sanitized, 2, "_raw_message").is_undefined());
+
+        // Without strip_ansi only the ends are trimmed
+        let trimmed = js_sys::Array::from(&sanitize_messages(logs.into(), false).unwrap());
+        assert_eq!(field(&trimmed, 0, "message").as_string().unwrap(), "\x1b[1;31mERROR\x1b[0m: disk full");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_timings() {
+        set_profiling(false);
+        merge_insert_logs(create_test_logs(10).into(), create_sorted_logs(2000, 10).into()).unwrap();
+        assert!(get_last_merge_timings().is_null());
+
+        set_profiling(true);
+        let merged = merge_insert_logs(create_test_logs(200).into(), create_sorted_logs(2000, 50).into());
+        let timings = get_last_merge_timings();
+        set_profiling(false);
+        merged.unwrap();
+
+        let field = |key: &str| js_sys::Reflect::get(&timings, &key.into()).unwrap().as_f64().unwrap();
+        assert_eq!(field("entry_count"), 250.0);
+        for phase in ["deserialize_ms", "merge_ms", "build_ms"] {
+            assert!(field(phase) >= 0.0 && field(phase) <= field("total_ms"), "{}", phase);
+        }
+        // Disabling discards the recorded timings
+        assert!(get_last_merge_timings().is_null());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_search_logs_multi() {
+        let logs = create_test_logs(4);
+        for (index, message) in ["error: connect timeout", "error: refused", "timeout waiting", "all fine"].iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"message".into(), &(*message).into()).unwrap();
+        }
+        let search = |terms: &[&str], mode: &str| -> Vec<u32> {
+            let terms: js_sys::Array = terms.iter().map(|term| JsValue::from(*term)).collect();
+            search_logs_multi(logs.clone().into(), terms.into(), mode).unwrap().to_vec()
+        };
+
+        assert_eq!(search(&["error", "timeout"], "and"), vec![0]);
+        assert_eq!(search(&["error", "timeout"], "or"), vec![0, 1, 2]);
+        assert_eq!(search(&["Error"], "or"), Vec::<u32>::new());
+        // Empty term lists: OR matches everything, AND nothing
+        assert_eq!(search(&[], "or"), vec![0, 1, 2, 3]);
+        assert_eq!(search(&[], "and"), Vec::<u32>::new());
+
+        assert!(search_logs_multi(logs.clone().into(), js_sys::Array::new().into(), "xor").is_err());
+        assert!(search_logs_multi(logs.into(), JsValue::from("error"), "and").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_export_filtered_ndjson() {
+        let logs = create_test_logs(3);
+        for (index, message) in ["Upload failed", "upload ok", "idle"].iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"message".into(), &(*message).into()).unwrap();
+        }
+
+        let ndjson = export_filtered_ndjson(logs.clone().into(), "upload", true).unwrap();
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(ndjson.ends_with('\n'));
+        let first = js_sys::JSON::parse(lines[0]).unwrap();
+        assert_eq!(js_sys::Reflect::get(&first, &"message".into()).unwrap().as_string().unwrap(), "Upload failed");
+
+        assert_eq!(export_filtered_ndjson(logs.clone().into(), "upload", false).unwrap().lines().count(), 1);
+        assert_eq!(export_filtered_ndjson(logs.clone().into(), "", false).unwrap().lines().count(), 3);
+        assert_eq!(export_filtered_ndjson(logs.clone().into(), "missing", false).unwrap(), "");
+
+        // Entries without a message: exporting, filtering and counting agree
+        let untitled = create_log_with_timestamp(4000.0);
+        js_sys::Reflect::delete_property(&untitled, &"message".into()).unwrap();
+        logs.push(&untitled);
+        for needle in ["", "upload"] {
+            let exported = export_filtered_ndjson(logs.clone().into(), needle, true).unwrap().lines().count();
+            let filtered = js_sys::Array::from(&filter_logs_containing(logs.clone().into(), needle, true).unwrap()).length();
+            let counted = count_logs_containing(logs.clone().into(), needle, true).unwrap();
+            assert_eq!(exported, filtered as usize, "needle {:?}", needle);
+            assert_eq!(exported, counted, "needle {:?}", needle);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_field_aliases() {
+        let foreign = |key: &str, unix_time: f64, message: &str| {
+            let log = js_sys::Object::new();
+            js_sys::Reflect::set(&log, &key.into(), &unix_time.into()).unwrap();
+            js_sys::Reflect::set(&log, &"msg".into(), &message.into()).unwrap();
+            log
+        };
+        let logs = js_sys::Array::new();
+        logs.push(&foreign("ts", 3000.0, "third"));
+        logs.push(&foreign("@timestamp", 1000.0, "first"));
+        // Canonical fields win over aliases
+        let both = create_log_with_timestamp(2000.0);
+        js_sys::Reflect::set(&both, &"ts".into(), &9999.0.into()).unwrap();
+        js_sys::Reflect::set(&both, &"msg".into(), &"ignored".into()).unwrap();
+        logs.push(&both);
+
+        let aliases = js_sys::JSON::parse(r#"{ "_unix_time": ["ts", "@timestamp"], "message": "msg" }"#).unwrap();
+        set_field_aliases(aliases).unwrap();
+        let merged = merge_insert_logs(logs.clone().into(), create_sorted_logs(4, 1).into());
+        set_field_aliases(JsValue::NULL).unwrap();
+        let merged = js_sys::Array::from(&merged.unwrap());
+
+        let get = |index: u32, key: &str| js_sys::Reflect::get(&merged.get(index), &key.into()).unwrap();
+        let times: Vec<f64> = merged.iter().map(|log| get_unix_time_from_log(&log)).collect();
+        assert_eq!(times, vec![1000.0, 2000.0, 3000.0, 4000.0]);
+        assert_eq!(get(0, "message").as_string().unwrap(), "first");
+        assert!(get(0, "msg").is_undefined());
+        assert!(get(0, "@timestamp").is_undefined());
+        assert_eq!(get(1, "message").as_string().unwrap(), "Test message");
+        assert_eq!(get(1, "ts").as_f64(), Some(9999.0));
+
+        // The caller's objects are not rewritten
+        assert!(js_sys::Reflect::has(&logs.get(0), &"ts".into()).unwrap());
+        assert!(!js_sys::Reflect::has(&logs.get(0), &"_unix_time".into()).unwrap());
+
+        let unknown = js_sys::JSON::parse(r#"{ "when": "ts" }"#).unwrap();
+        assert!(set_field_aliases(unknown).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_caches() {
+        let handle_a = cache_logs(create_logs_with_timestamps(&[3.0, 1.0, 5.0]).into()).unwrap();
+        let handle_b = cache_logs(create_logs_with_timestamps(&[2.0, 4.0]).into()).unwrap();
+        let merged = merge_caches(handle_a, handle_b).unwrap();
+        assert!(merged != handle_a && merged != handle_b);
+
+        let chunk_times = |handle: u32, chunk_index: usize| -> (Vec<f64>, bool) {
+            let chunk = get_cache_chunk(handle, chunk_index, 3).unwrap();
+            let logs = js_sys::Array::from(&js_sys::Reflect::get(&chunk, &"logs".into()).unwrap());
+            let has_more = js_sys::Reflect::get(&chunk, &"has_more".into()).unwrap().as_bool().unwrap();
+            (logs.iter().map(|log| get_unix_time_from_log(&log) / 1000.0).collect(), has_more)
+        };
+        assert_eq!(chunk_times(merged, 0), (vec![1.0, 2.0, 3.0], true));
+        assert_eq!(chunk_times(merged, 1), (vec![4.0, 5.0], false));
+        // Inputs stay usable after the merge
+        assert_eq!(chunk_times(handle_a, 0), (vec![1.0, 3.0, 5.0], false));
+        // Including merging a batch with itself
+        let doubled = merge_caches(handle_b, handle_b).unwrap();
+        assert_eq!(chunk_times(doubled, 0), (vec![2.0, 2.0, 4.0], true));
+        assert!(free_cache(doubled));
+
+        for handle in [handle_a, handle_b, merged] {
+            assert!(free_cache(handle));
+        }
+        assert!(!free_cache(merged));
+        assert!(get_cache_chunk(merged, 0, 3).is_err());
+        assert!(merge_caches(handle_a, handle_b).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_exact_message() {
+        let logs = create_test_logs(5);
+        for (index, message) in ["retrying", "retrying in 5s", "Retrying", "retrying"].iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"message".into(), &(*message).into()).unwrap();
+        }
+        js_sys::Reflect::delete_property(&logs.get(4).into(), &"message".into()).unwrap();
+
+        assert_eq!(find_exact_message(logs.clone().into(), "retrying").unwrap().to_vec(), vec![0, 3]);
+        assert_eq!(find_exact_message(logs.clone().into(), "retry").unwrap().to_vec(), Vec::<u32>::new());
+        assert_eq!(find_exact_message(logs.into(), "").unwrap().to_vec(), Vec::<u32>::new());
+        assert!(find_exact_message(JsValue::from("retrying"), "retrying").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_insert_logs_pinned() {
+        let existing = create_logs_with_timestamps(&[1.0, 3.0]);
+        let new_logs = create_logs_with_timestamps(&[2.0, 4.0]);
+        for (index, log_item) in new_logs.iter().enumerate() {
+            js_sys::Reflect::set(&log_item, &"_sequence".into(), &JsValue::from(100 + index as u32)).unwrap();
+        }
+        let times = |logs: &JsValue| -> Vec<f64> {
+            js_sys::Array::from(logs).iter().map(|log| get_unix_time_from_log(&log) / 1000.0).collect()
+        };
+
+        let pinned = merge_insert_logs_pinned(existing.clone().into(), new_logs.clone().into(), 101).unwrap();
+        assert_eq!(times(&pinned), vec![4.0, 1.0, 2.0, 3.0]);
+
+        // Unknown sequence: a plain merge
+        let unpinned = merge_insert_logs_pinned(existing.clone().into(), new_logs.into(), 999).unwrap();
+        assert_eq!(times(&unpinned), vec![1.0, 2.0, 3.0, 4.0]);
+
+        // An empty side must not reorder the caller's own array
+        js_sys::Reflect::set(&existing.get(1), &"_sequence".into(), &JsValue::from(7)).unwrap();
+        let pinned = merge_insert_logs_pinned(existing.clone().into(), js_sys::Array::new().into(), 7).unwrap();
+        assert_eq!(times(&pinned), vec![3.0, 1.0]);
+        assert_eq!(times(&existing.into()), vec![1.0, 3.0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_estimate_single_log_bytes() {
+        let logs = create_test_logs(2);
+        js_sys::Reflect::set(&logs.get(1), &"payload".into(), &"x".repeat(1000).into()).unwrap();
+
+        let small = estimate_single_log_bytes(logs.get(0));
+        let large = estimate_single_log_bytes(logs.get(1));
+        assert!(small > 0);
+        assert!(large >= small + 1000);
+
+        // Same figures top_largest_logs ranks by
+        let top = js_sys::Array::from(&top_largest_logs(logs.into(), 1).unwrap());
+        let top_bytes = js_sys::Reflect::get(&top.get(0), &"estimated_bytes".into()).unwrap().as_f64().unwrap();
+        assert_eq!(top_bytes as usize, large);
+
+        assert_eq!(estimate_single_log_bytes(JsValue::from(42)), 0);
+        assert_eq!(estimate_single_log_bytes(JsValue::UNDEFINED), 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_near_duplicates() {
+        // Two retries 5ms apart, then the same message again 5s later, with another line in between
+        let logs = create_test_logs(4);
+        let entries = [(100.0, "retry upload"), (100.002, "other"), (100.005, "retry upload"), (105.005, "retry upload")];
+        for (index, (unix_time, message)) in entries.iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"_unix_time".into(), &(*unix_time).into()).unwrap();
+            js_sys::Reflect::set(&logs.get(index as u32), &"message".into(), &(*message).into()).unwrap();
+        }
+        let fields: js_sys::Array = ["message"].iter().map(|field| JsValue::from(*field)).collect();
+
+        let result = js_sys::Array::from(&merge_near_duplicates(logs.into(), 10.0, fields.clone().into()).unwrap());
+        assert_eq!(result.length(), 3);
+        let message = |index: u32| js_sys::Reflect::get(&result.get(index), &"message".into()).unwrap().as_string().unwrap();
+        let coalesced = |index: u32| js_sys::Reflect::get(&result.get(index), &"_coalesced_count".into()).unwrap().as_f64();
+        assert_eq!(message(0), "other");
+        // The close pair keeps its latest entry, at that entry's position
+        assert_eq!(message(1), "retry upload");
+        assert_eq!(get_unix_time_from_log(&result.get(1)), 100.005);
+        assert_eq!(coalesced(1), Some(2.0));
+        // The one 5s later stays separate
+        assert_eq!(coalesced(2), None);
+
+        assert!(merge_near_duplicates(js_sys::Array::new().into(), -1.0, fields.into()).is_err());
+        assert!(merge_near_duplicates(js_sys::Array::new().into(), 10.0, js_sys::Array::new().into()).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_ingestion_clock_is_monotonic() {
+        reset_ingestion_clock();
+        let wall_clock = js_sys::Date::now() / 1000.0;
+        let first = next_ingestion_time();
+        assert!((first - wall_clock).abs() < 60.0);
+
+        let mut previous = first;
+        for _ in 0..100 {
+            let next = next_ingestion_time();
+            assert!(next > previous);
+            previous = next;
+        }
+
+        // Entries without _unix_time sort first and are stamped from the same clock
+        let untimed = create_test_logs(2);
+        for log_item in untimed.iter() {
+            js_sys::Reflect::delete_property(&log_item.into(), &"_unix_time".into()).unwrap();
+        }
+        let merged = js_sys::Array::from(&merge_insert_logs(create_test_logs(1).into(), untimed.into()).unwrap());
+        let stamped: Vec<f64> = merged.iter().take(2).map(|log| get_unix_time_from_log(&log)).collect();
+        assert!(stamped[0] > previous);
+        assert!(stamped[1] > stamped[0]);
+
+        reset_ingestion_clock();
+    }
+
+    #[wasm_bindgen_test]
+    fn test_split_by_source() {
+        let merged = merge_insert_logs_tagged(
+            create_logs_with_timestamps(&[1.0, 3.0]).into(),
+            create_logs_with_timestamps(&[2.0, 4.0]).into(),
+            "disk",
+            "live",
+        ).unwrap();
+        let merged = js_sys::Array::from(&merged);
+        merged.push(&create_log_with_timestamp(5000.0));
+
+        let split = split_by_source(merged.into()).unwrap();
+        let bucket_times = |source: &str| -> Vec<f64> {
+            let bucket = js_sys::Array::from(&js_sys::Reflect::get(&split, &source.into()).unwrap());
+            bucket.iter().map(|log| get_unix_time_from_log(&log) / 1000.0).collect()
+        };
+        assert_eq!(bucket_times("disk"), vec![1.0, 3.0]);
+        assert_eq!(bucket_times("live"), vec![2.0, 4.0]);
+        assert_eq!(bucket_times("untagged"), vec![5.0]);
+        assert_eq!(js_sys::Object::keys(&split.into()).length(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_try_ensure_memory() {
+        let usage = get_memory_usage_lite();
+        let total_bytes = js_sys::Reflect::get(&usage, &"total_bytes".into()).unwrap().as_f64().unwrap() as usize;
+        let field = |outcome: &JsValue, name: &str| js_sys::Reflect::get(outcome, &name.into()).unwrap();
+
+        let fits = try_ensure_memory(0);
+        assert_eq!(field(&fits, "success").as_bool(), Some(true));
+        assert_eq!(field(&fits, "grew_pages").as_f64(), Some(0.0));
+        assert!(field(&fits, "failure_reason").is_null());
+
+        // An engine that refuses to grow
+        let memory = wasm_bindgen::memory();
+        let failing_grow = js_sys::Function::new_with_args("pages", "throw new RangeError('out of memory');");
+        js_sys::Reflect::set(&memory, &"grow".into(), &failing_grow).unwrap();
+        let failed = try_ensure_memory(total_bytes);
+        js_sys::Reflect::delete_property(&memory.into(), &"grow".into()).unwrap();
+
+        assert_eq!(field(&failed, "success").as_bool(), Some(false));
+        assert_eq!(field(&failed, "failure_reason").as_string().as_deref(), Some("growth_failed"));
+        assert_eq!(field(&failed, "suggested_retry_bytes").as_f64(), Some((total_bytes / 2) as f64));
+
+        // Under a soft cap, the suggestion is a need that fits without growing
+        set_memory_soft_cap_bytes(total_bytes);
+        let capped = try_ensure_memory(total_bytes);
+        let suggested = field(&capped, "suggested_retry_bytes").as_f64().unwrap() as usize;
+        let retried = try_ensure_memory(suggested);
+        set_memory_soft_cap_bytes(0);
+
+        assert_eq!(field(&capped, "failure_reason").as_string().as_deref(), Some("soft_cap"));
+        assert!(suggested < total_bytes);
+        assert_eq!(field(&retried, "success").as_bool(), Some(true));
+        assert_eq!(field(&retried, "grew_pages").as_f64(), Some(0.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_get_tracker_drift() {
+        // Deserializing a batch registers its size with the tracker
+        merge_insert_logs(create_test_logs(50).into(), create_test_logs(50).into()).unwrap();
+
+        let drift = get_tracker_drift();
+        let field = |name: &str| js_sys::Reflect::get(&drift, &name.into()).unwrap().as_f64().unwrap();
+        let total = field("browser_total_bytes");
+        let active = field("tracker_active_bytes");
+
+        assert!(total > 0.0);
+        assert_eq!(total as usize % 65536, 0);
+        assert!(active > 0.0);
+        assert!(field("tracker_peak_bytes") >= active);
+        assert_eq!(field("drift_ratio"), active / total);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_insert_logs_with_error_rate() {
+        let existing = create_logs_with_timestamps(&[1.0, 3.0, 5.0]);
+        let new_logs = create_logs_with_timestamps(&[2.0, 4.0, 6.0]);
+        // In merged order: 1 info, 2 ERROR, 3 info, 4 fatal, 5 warn, 6 info
+        js_sys::Reflect::set(&new_logs.get(0), &"level".into(), &"ERROR".into()).unwrap();
+        js_sys::Reflect::set(&new_logs.get(1), &"level".into(), &"fatal".into()).unwrap();
+        js_sys::Reflect::set(&existing.get(2), &"level".into(), &"warn".into()).unwrap();
+        let rate = |window: usize| -> f64 {
+            let result = merge_insert_logs_with_error_rate(existing.clone().into(), new_logs.clone().into(), window).unwrap();
+            assert_eq!(js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap()).length(), 6);
+            js_sys::Reflect::get(&result, &"error_rate".into()).unwrap().as_f64().unwrap()
+        };
+
+        assert_eq!(rate(2), 0.0);
+        assert_eq!(rate(3), 1.0 / 3.0);
+        assert_eq!(rate(5), 0.4);
+        // A window larger than the result uses every entry
+        assert_eq!(rate(100), 2.0 / 6.0);
+        assert_eq!(rate(0), 0.0);
+
+        // A null side counts as empty
+        let result = merge_insert_logs_with_error_rate(JsValue::NULL, new_logs.clone().into(), 3).unwrap();
+        assert_eq!(js_sys::Array::from(&js_sys::Reflect::get(&result, &"logs".into()).unwrap()).length(), 3);
+        assert_eq!(js_sys::Reflect::get(&result, &"error_rate".into()).unwrap().as_f64(), Some(2.0 / 3.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_register_level() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0, 4.0]);
+        for (index, level) in ["notice", "CRITICAL", "debug", "error"].iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"level".into(), &(*level).into()).unwrap();
+        }
+        let error_rate = || -> f64 {
+            let result = merge_insert_logs_with_error_rate(logs.clone().into(), js_sys::Array::new().into(), 4).unwrap();
+            js_sys::Reflect::get(&result, &"error_rate".into()).unwrap().as_f64().unwrap()
+        };
+
+        // Unknown names rank as info
+        assert_eq!(error_rate(), 0.25);
+        register_level("Critical", 5);
+        register_level("notice", 2);
+        assert_eq!(error_rate(), 0.5);
+        // Built-in levels can be overridden
+        register_level("debug", 4);
+        let overridden = error_rate();
+        clear_registered_levels();
+
+        assert_eq!(overridden, 0.75);
+        assert_eq!(error_rate(), 0.25);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_buffer_reuse() {
+        let allocation_count = || -> f64 {
+            js_sys::Map::from(get_allocation_stats()).get(&"allocation_count".into()).as_f64().unwrap()
+        };
+        let merge = |start: u32| -> js_sys::Array {
+            let merged = merge_insert_logs(create_sorted_logs(start, 20).into(), create_sorted_logs(start + 10, 20).into()).unwrap();
+            js_sys::Array::from(&merged)
+        };
+
+        // Each merge resets the tracker, so the count is that of the last merge alone
+        merge(1);
+        let fresh = allocation_count();
+        set_buffer_reuse(true);
+        merge(1);
+        let mut reused = Vec::new();
+        for start in 100..103 {
+            let merged = merge(start);
+            reused.push(allocation_count());
+            // Nothing from an earlier merge leaks into the reused buffer
+            assert_eq!(merged.length(), 40);
+            assert_eq!(get_unix_time_from_log(&merged.get(0)), start as f64 * 1000.0);
+        }
+        set_buffer_reuse(false);
+
+        assert!(reused.iter().all(|&count| count == fresh - 1.0));
+        merge(1);
+        assert_eq!(allocation_count(), fresh);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_index_of_sequence() {
+        let logs = create_test_logs(6);
+        for (index, sequence) in [2, 5, 7, 7, 9, 12].iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"_sequence".into(), &JsValue::from(*sequence)).unwrap();
+        }
+        let logs: JsValue = logs.into();
+
+        assert_eq!(index_of_sequence(&logs, 2), 0);
+        assert_eq!(index_of_sequence(&logs, 9), 4);
+        assert_eq!(index_of_sequence(&logs, 12), 5);
+        // Duplicates resolve to the first
+        assert_eq!(index_of_sequence(&logs, 7), 2);
+        assert_eq!(index_of_sequence(&logs, 1), -1);
+        assert_eq!(index_of_sequence(&logs, 6), -1);
+        assert_eq!(index_of_sequence(&logs, 13), -1);
+
+        assert_eq!(index_of_sequence(&js_sys::Array::new().into(), 0), -1);
+        assert_eq!(index_of_sequence(&JsValue::NULL, 0), -1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_merge_output_is_tracked() {
+        let stat = |name: &str| -> f64 {
+            js_sys::Map::from(get_allocation_stats()).get(&name.into()).as_f64().unwrap()
+        };
+        let existing = create_sorted_logs(1, 30);
+        let new_logs = create_sorted_logs(100, 30);
+        let input_bytes: usize = existing.iter().chain(new_logs.iter()).map(estimate_single_log_bytes).sum();
+
+        // Inputs are tracked when deserialized, and the emitted result once more
+        merge_insert_logs(existing.clone().into(), new_logs.clone().into()).unwrap();
+        assert!(stat("active_bytes") - stat("retained_bytes") >= 2.0 * input_bytes as f64);
+        // ...without counting as an allocation: only the two inputs and the merge buffer do
+        assert_eq!(stat("allocation_count"), 3.0);
+
+        // The result emitted from the cache is counted once, with the cache, until it is cleared
+        clear_log_cache();
+        let baseline = stat("retained_bytes");
+        merge_and_get_chunk(existing.clone().into(), new_logs.clone().into(), 0, 30).unwrap();
+        let served = stat("retained_bytes");
+        for chunk_index in [1, 0, 1] {
+            merge_and_get_chunk(existing.clone().into(), new_logs.clone().into(), chunk_index, 30).unwrap();
+        }
+        let reread = stat("retained_bytes");
+        clear_log_cache();
+
+        // Cached logs plus their emitted objects
+        assert_eq!(served - baseline, 2.0 * input_bytes as f64);
+        assert_eq!(reread, served);
+        assert_eq!(stat("retained_bytes"), baseline);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_find_invalid_utf8() {
+        let logs = create_test_logs(4);
+        // Built from code units, since Rust strings can't hold lone surrogates
+        let from_units = |units: &[u16]| -> JsValue { js_sys::JsString::from_char_code(units).into() };
+        js_sys::Reflect::set(&logs.get(0), &"message".into(), &"caf\u{e9} \u{1F600}".into()).unwrap();
+        js_sys::Reflect::set(&logs.get(1), &"message".into(), &from_units(&[0x61, 0xD83D])).unwrap();
+        js_sys::Reflect::set(&logs.get(2), &"user".into(), &from_units(&[0xDE00, 0x61])).unwrap();
+        js_sys::Reflect::set(&logs.get(3), &"message".into(), &"bad \u{FFFD} byte".into()).unwrap();
+
+        let issues = js_sys::Array::from(&find_invalid_utf8(logs.into()).unwrap());
+        let issue = |position: u32| -> (f64, String, String) {
+            let item = issues.get(position);
+            let get = |key: &str| js_sys::Reflect::get(&item, &key.into()).unwrap();
+            (get("index").as_f64().unwrap(), get("field").as_string().unwrap(), get("reason").as_string().unwrap())
+        };
+
+        assert_eq!(issues.length(), 3);
+        assert_eq!(issue(0), (1.0, "message".to_string(), "truncated_surrogate".to_string()));
+        assert_eq!(issue(1), (2.0, "user".to_string(), "lone_surrogate".to_string()));
+        assert_eq!(issue(2), (3.0, "message".to_string(), "replacement_character".to_string()));
+        assert!(find_invalid_utf8(JsValue::from("logs")).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_compact_output_outside_whole_arrays() {
+        // No entry carries a sequence, so every one relies on its fallback index
+        let unsequenced = |times: &[f64]| -> js_sys::Array {
+            let logs = create_logs_with_timestamps(times);
+            for log_item in logs.iter() {
+                js_sys::Reflect::delete_property(&log_item.into(), &"_sequence".into()).unwrap();
+            }
+            logs
+        };
+        // What a reader gets back, applying the position default where `_sequence` is left out
+        let sequences = |logs: &JsValue| -> Vec<u32> {
+            js_sys::Array::from(logs).iter().enumerate().map(|(position, log)| {
+                js_sys::Reflect::get(&log, &"_sequence".into()).unwrap().as_f64().map_or(position as u32, |sequence| sequence as u32)
+            }).collect()
+        };
+        let existing = unsequenced(&[1.0, 3.0, 5.0]);
+        let new_logs = unsequenced(&[2.0, 4.0, 6.0]);
+
+        set_compact_output(true);
+        clear_log_cache();
+        let chunk = merge_and_get_chunk(existing.clone().into(), new_logs.clone().into(), 1, 2).unwrap();
+        let chunk_logs = js_sys::Reflect::get(&chunk, &"logs".into()).unwrap();
+        set_cache_order("desc").unwrap();
+        let descending = merge_and_get_chunk(existing.into(), new_logs.into(), 0, 2).unwrap();
+        let descending_logs = js_sys::Reflect::get(&descending, &"logs".into()).unwrap();
+        set_cache_order("asc").unwrap();
+        clear_log_cache();
+        let tail = tail_logs(unsequenced(&[1.0, 2.0, 3.0, 4.0]).into(), 2).unwrap();
+        let pinned = merge_insert_logs_pinned(unsequenced(&[1.0, 2.0, 3.0]).into(), js_sys::Array::new().into(), 2).unwrap();
+        set_compact_output(false);
+
+        assert_eq!(sequences(&chunk_logs), vec![2, 3]);
+        assert_eq!(sequences(&descending_logs), vec![5, 4]);
+        assert_eq!(sequences(&tail), vec![2, 3]);
+        assert_eq!(sequences(&pinned), vec![2, 0, 1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_measure_logs_serialized_bytes() {
+        // Missing named fields serialize as null, so every one is given; times are
+        // fractional since serde_json writes whole floats as "1.0" where JS writes "1"
+        let logs: JsValue = js_sys::JSON::parse(r#"[
+            {"level": "info", "message": "say \"hi\"", "time": "12:00", "behavior": null, "_unix_time": 1.5, "_sequence": 0},
+            {"level": "error", "message": "disk full", "time": null, "behavior": null, "_unix_time": 2.25, "_sequence": 1, "user": "alice"}
+        ]"#).unwrap();
+        // One JSON line per entry; the text is ASCII, so UTF-16 length equals bytes
+        let expected: u32 = js_sys::Array::from(&logs).iter()
+            .map(|log| js_sys::JSON::stringify(&log).unwrap().length() + 1)
+            .sum();
+
+        let measurement = js_sys::Map::from(measure_logs_serialized_bytes(logs).unwrap());
+        assert_eq!(measurement.get(&"actual_bytes".into()).as_f64(), Some(expected as f64));
+        assert_eq!(measurement.get(&"log_count".into()).as_f64(), Some(2.0));
+        let heuristic = measurement.get(&"heuristic_bytes".into()).as_f64().unwrap();
+        assert_eq!(measurement.get(&"ratio".into()).as_f64(), Some(heuristic / expected as f64));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_verbose_logging_is_opt_in() {
+        // Capture console.log, where verbose diagnostics go
+        let console = js_sys::Reflect::get(&js_sys::global(), &"console".into()).unwrap();
+        let original_log = js_sys::Reflect::get(&console, &"log".into()).unwrap();
+        let captured = js_sys::Array::new();
+        let capture = js_sys::Function::new_with_args("lines", "return (line) => { lines.push(line); }")
+            .call1(&JsValue::NULL, &captured).unwrap();
+        let merge = || {
+            merge_insert_logs(create_logs_with_timestamps(&[1.0, 3.0]).into(), create_logs_with_timestamps(&[2.0]).into()).unwrap();
+        };
+
+        js_sys::Reflect::set(&console, &"log".into(), &capture).unwrap();
+        merge();
+        let quiet_lines = captured.length();
+        set_verbose_logging(true);
+        merge();
+        set_verbose_logging(false);
+        js_sys::Reflect::set(&console, &"log".into(), &original_log).unwrap();
+
+        assert_eq!(quiet_lines, 0);
+        assert!(captured.iter().any(|line| line.as_string().unwrap().starts_with("Merged log array has 3 entries")));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sorted_check_compares_sequences() {
+        let build = |entries: &[(f64, u32)]| {
+            let logs = js_sys::Array::new();
+            for (time, sequence) in entries {
+                logs.push(&create_log_with_sequence(*time, *sequence));
+            }
+            logs
+        };
+        let order = |result: JsValue| -> Vec<(f64, u32)> {
+            js_sys::Array::from(&result).iter()
+                .map(|log| (get_unix_time_from_log(&log) / 1000.0, get_sequence_from_log(&log)))
+                .collect()
+        };
+
+        // Ascending by time, but not by sequence within equal timestamps: the
+        // sortedness check must not let this skip the sort
+        let existing = build(&[(1.0, 5), (1.0, 2), (2.0, 0)]);
+        let merged = order(merge_insert_logs(existing.into(), build(&[(3.0, 9)]).into()).unwrap());
+        assert_eq!(merged, vec![(1.0, 2), (1.0, 5), (2.0, 0), (3.0, 9)]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_typed_field_values() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0]);
+        let user_ids: [JsValue; 3] = [1.into(), "1".into(), 2.into()];
+        for (index, user_id) in user_ids.iter().enumerate() {
+            js_sys::Reflect::set(&logs.get(index as u32), &"user_id".into(), user_id).unwrap();
+        }
+
+        // Distinct values are reported as strings, so 1 and "1" are one value
+        let distinct = js_sys::Array::from(&distinct_field_values(logs.clone().into(), "user_id", 0).unwrap());
+        let distinct: Vec<String> = distinct.iter().map(|value| value.as_string().unwrap()).collect();
+        assert_eq!(distinct, vec!["1", "2"]);
+
+        // ...but as keys they stay apart
+        let keys = js_sys::Array::of1(&"user_id".into());
+        let deduped = merge_dedup_last_wins(logs.clone().into(), js_sys::Array::new().into(), keys.clone().into()).unwrap();
+        assert_eq!(js_sys::Array::from(&deduped).length(), 3);
+        let coalesced = merge_near_duplicates(logs.into(), 5000.0, keys.into()).unwrap();
+        assert_eq!(js_sys::Array::from(&coalesced).length(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_tail_logs() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0, 4.0]);
+        for log_item in logs.iter() {
+            js_sys::Reflect::delete_property(&log_item.into(), &"_sequence".into()).unwrap();
+        }
+        let tail = |n: usize| -> Vec<(f64, u32)> {
+            js_sys::Array::from(&tail_logs(logs.clone().into(), n).unwrap()).iter()
+                .map(|log| (get_unix_time_from_log(&log) / 1000.0, get_sequence_from_log(&log)))
+                .collect()
+        };
+
+        // Fallback sequences are the entries' indices in the full array
+        assert_eq!(tail(2), vec![(3.0, 2), (4.0, 3)]);
+        assert_eq!(tail(10), vec![(1.0, 0), (2.0, 1), (3.0, 2), (4.0, 3)]);
+        assert_eq!(tail(0), vec![]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_min_growth_pages() {
+        let usage = get_memory_usage_lite();
+        let total_bytes = js_sys::Reflect::get(&usage, &"total_bytes".into()).unwrap().as_f64().unwrap() as usize;
+
+        // Record grow calls instead of growing; grows above `limit` pages fail
+        let grows = js_sys::Array::new();
+        let limit = js_sys::Object::new();
+        let memory = wasm_bindgen::memory();
+        let fake_grow = js_sys::Function::new_with_args(
+            "grows, limit, pages",
+            "if (pages) grows.push(pages); return pages > limit.pages ? 4294967295 : 0;",
+        ).bind2(&JsValue::NULL, &grows, &limit);
+        js_sys::Reflect::set(&memory, &"grow".into(), &fake_grow).unwrap();
+        let grow_calls = |min_pages: u32, limit_pages: f64| -> (bool, Vec<f64>) {
+            grows.set_length(0);
+            js_sys::Reflect::set(&limit, &"pages".into(), &limit_pages.into()).unwrap();
+            set_min_growth_pages(min_pages);
+            let grown = ensure_sufficient_memory(total_bytes);
+            (grown, grows.iter().map(|pages| pages.as_f64().unwrap()).collect())
+        };
+
+        // Below the need, the minimum changes nothing
+        let exact = grow_calls(1, f64::INFINITY);
+        let needed = exact.1.first().copied().unwrap_or_default();
+        // Above it, growth is rounded up to the minimum...
+        let rounded = grow_calls(needed as u32 + 100, f64::INFINITY);
+        // ...and retried with the exact need when the larger grow fails
+        let retried = grow_calls(needed as u32 + 100, needed);
+        set_min_growth_pages(16);
+        js_sys::Reflect::delete_property(&memory.into(), &"grow".into()).unwrap();
+
+        assert_eq!(exact, (true, vec![needed]));
+        assert!(needed > 0.0);
+        assert_eq!(rounded, (true, vec![needed + 100.0]));
+        assert_eq!(retried, (true, vec![needed + 100.0, needed]));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_memory_usage_lite_matches_full_report() {
+        merge_insert_logs(create_test_logs(20).into(), create_sorted_logs(5000, 20).into()).unwrap();
+
+        let lite = get_memory_usage_lite();
+        let full = js_sys::Map::from(get_memory_usage());
+        let lite_field = |name: &str| js_sys::Reflect::get(&lite, &name.into()).unwrap().as_f64().unwrap();
+        for name in ["total_bytes", "used_bytes", "utilization", "current_pages"] {
+            assert_eq!(Some(lite_field(name)), full.get(&name.into()).as_f64(), "{} differs", name);
+        }
+        assert!((0.0..=1.0).contains(&lite_field("utilization")));
+        assert!(lite_field("used_bytes") > 0.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_filter_logs_by_predicate() {
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0, 4.0]);
+
+        // Keep entries after the second one; order is preserved
+        let predicate = js_sys::Function::new_with_args("log", "return log._unix_time > 2000");
+        let result = filter_logs_by(logs.clone().into(), predicate).unwrap();
+        let result_array = js_sys::Array::from(&result);
+        assert_eq!(result_array.length(), 2);
+        assert_eq!(get_unix_time_from_log(&result_array.get(0)), 3000.0);
+        assert_eq!(get_unix_time_from_log(&result_array.get(1)), 4000.0);
+
+        // A throwing predicate drops the entry instead of failing the call
+        let predicate = js_sys::Function::new_with_args("log", "if (log._unix_time === 1000) throw new Error('boom'); return true");
+        let result = filter_logs_by(logs.into(), predicate).unwrap();
+        assert_eq!(js_sys::Array::from(&result).length(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_large_integer_extra_fields() {
+        // 2^53 + 1 cannot be represented exactly as a JS number
+        let trace_id: u64 = 9_007_199_254_740_993;
+        let merge_trace_log = || {
+            let log = create_log_with_timestamp(1000.0);
+            js_sys::Reflect::set(&log, &"trace_id".into(), &js_sys::BigInt::from(trace_id).into()).unwrap();
+            let logs = js_sys::Array::new();
+            logs.push(&log);
+            let result = merge_insert_logs(create_logs_with_timestamps(&[2.0]).into(), logs.into()).unwrap();
+            js_sys::Reflect::get(&js_sys::Array::from(&result).get(0), &"trace_id".into()).unwrap()
+        };
+
+        // Default: emitted as an exact decimal string
+        assert_eq!(merge_trace_log().as_string(), Some(trace_id.to_string()));
+
+        // BigInt mode: emitted as an equal BigInt
+        set_bigint_mode(true);
+        let value = merge_trace_log();
+        set_bigint_mode(false);
+        assert!(value.is_bigint());
+        assert_eq!(u64::try_from(value).unwrap(), trace_id);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_preserve_complex_fields() {
+        let payload = js_sys::Object::new();
+        js_sys::Reflect::set(&payload, &"nested".into(), &js_sys::Array::of1(&1.into())).unwrap();
+        let merge_payload_log = || {
+            let log = create_log_with_timestamp(1000.0);
+            js_sys::Reflect::set(&log, &"payload".into(), &payload).unwrap();
+            let logs = js_sys::Array::new();
+            logs.push(&log);
+            let result = merge_insert_logs(create_logs_with_timestamps(&[2.0]).into(), logs.into()).unwrap();
+            js_sys::Reflect::get(&js_sys::Array::from(&result).get(0), &"payload".into()).unwrap()
+        };
+
+        // Default: rebuilt as an equivalent but distinct object
+        assert!(!js_sys::Object::is(&merge_payload_log(), &payload));
+
+        // Preserved: the very same object comes back out
+        set_preserve_complex_fields(true);
+        let value = merge_payload_log();
+        set_preserve_complex_fields(false);
+        assert!(js_sys::Object::is(&value, &payload));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_non_finite_unix_times() {
+        // Absurd timestamps are clamped instead of leaking into the output
+        let logs1 = create_logs_with_timestamps(&[1.0, 2.0]);
+        let logs2 = js_sys::Array::new();
+        logs2.push(&create_log_with_timestamp(f64::INFINITY));
+        logs2.push(&create_log_with_timestamp(f64::NAN));
+
+        let result = merge_insert_logs(logs1.into(), logs2.into()).unwrap();
+        let result_array = js_sys::Array::from(&result);
+        assert_eq!(result_array.length(), 4);
+
+        let times: Vec<f64> = (0..4).map(|i| get_unix_time_from_log(&result_array.get(i))).collect();
+        assert!(times.iter().all(|t| t.is_finite()), "Non-finite timestamp in output");
+
+        // NaN sorts first as the lower bound, infinity last as the upper bound
+        assert_eq!(times[0], 0.0);
+        assert!(times[3] > times[2]);
+        for i in 0..3 {
+            assert!(times[i] <= times[i + 1], "Logs not in chronological order");
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_diagnostic_sink() {
+        // Collect diagnostic codes on a global the test can inspect
+        let sink = js_sys::Function::new_with_args("d", "(globalThis.__codes = globalThis.__codes || []).push(d.code)");
+        set_diagnostic_sink(sink.into());
+
+        let logs1 = create_logs_with_timestamps(&[1.0]);
+        let logs2 = js_sys::Array::new();
+        logs2.push(&create_log_with_timestamp(f64::NAN));
+        merge_insert_logs(logs1.into(), logs2.into()).unwrap();
+        set_diagnostic_sink(JsValue::undefined());
+
+        let codes = js_sys::Reflect::get(&js_sys::global(), &"__codes".into()).unwrap();
+        let codes = js_sys::Array::from(&codes);
+        assert!(codes.includes(&"UNIX_TIME_CLAMPED".into(), 0), "Sink did not receive the warning code");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_empty_filter_result_is_not_reported() {
+        let sink = js_sys::Function::new_with_args("d", "(globalThis.__empty_codes = globalThis.__empty_codes || []).push(d.code)");
+        set_diagnostic_sink(sink.into());
+
+        let logs = create_logs_with_timestamps(&[1.0, 2.0]);
+        let result = filter_logs_containing(logs.into(), "no such text", false);
+        set_diagnostic_sink(JsValue::undefined());
+
+        assert_eq!(js_sys::Array::from(&result.unwrap()).length(), 0);
+        let codes = js_sys::Reflect::get(&js_sys::global(), &"__empty_codes".into()).unwrap();
+        assert!(codes.is_undefined(), "Filtering everything out should not raise a diagnostic");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_output_construction_is_preserved() {
+        let full_log = |time: &str, unix_time: f64, sequence: u32| {
+            let log = js_sys::Object::new();
+            let set = |key: &str, value: JsValue| { js_sys::Reflect::set(&log, &key.into(), &value).unwrap(); };
+            set("level", "warn".into());
+            set("message", "disk almost full".into());
+            set("time", time.into());
+            set("behavior", "alert".into());
+            set("_sequence", sequence.into());
+            set("_unix_time", unix_time.into());
+            set("_original_time", "2024-01-01T10:20:30.123Z".into());
+            set("_visible", true.into());
+            set("_height", 24.5.into());
+            set("zeta", "last".into());
+            set("alpha", 42.into());
+            set("flag", false.into());
+            set("nothing", JsValue::NULL);
+            set("nested", js_sys::JSON::parse("{\"k\":[1,2]}").unwrap());
+            log
+        };
+        let existing = js_sys::Array::new();
+        existing.push(&full_log("10:20:30", 1000.0, 1));
+        let new = js_sys::Array::new();
+        new.push(&full_log("2024-01-01T10:20:31.500+00:00", 1001.0, 2));
+
+        let result = js_sys::Array::from(&merge_insert_logs(existing.into(), new.into()).unwrap());
+        assert_eq!(result.length(), 2);
+
+        let expected_keys = ["level", "message", "time", "_sequence", "_unix_time", "behavior",
+                             "_original_time", "_visible", "_height",
+                             "alpha", "flag", "nested", "nothing", "zeta"];
+        for (entry, time, unix_time, sequence) in [(result.get(0), "10:20:30", 1000.0, 1.0),
+                                                    (result.get(1), "10:20:31", 1001.0, 2.0)] {
+            // Named fields first, then extra fields in alphabetical order
+            let keys: Vec<String> = js_sys::Object::keys(&js_sys::Object::from(entry.clone()))
+                .iter().map(|key| key.as_string().unwrap()).collect();
+            assert_eq!(keys, expected_keys);
+
+            let get = |key: &str| js_sys::Reflect::get(&entry, &key.into()).unwrap();
+            assert_eq!(get("level").as_string().as_deref(), Some("warn"));
+            assert_eq!(get("message").as_string().as_deref(), Some("disk almost full"));
+            assert_eq!(get("time").as_string().as_deref(), Some(time));
+            assert_eq!(get("_sequence").as_f64(), Some(sequence));
+            assert_eq!(get("_unix_time").as_f64(), Some(unix_time));
+            assert_eq!(get("behavior").as_string().as_deref(), Some("alert"));
+            assert_eq!(get("_original_time").as_string().as_deref(), Some("2024-01-01T10:20:30.123Z"));
+            assert_eq!(get("_visible").as_bool(), Some(true));
+            assert_eq!(get("_height").as_f64(), Some(24.5));
+            assert_eq!(get("alpha").as_f64(), Some(42.0));
+            assert_eq!(get("flag").as_bool(), Some(false));
+            assert!(get("nothing").is_null());
+            // Complex values are converted by serde_wasm_bindgen, which turns objects into Maps
+            let nested = js_sys::Map::from(get("nested"));
+            assert!(wasm_bindgen::JsCast::is_instance_of::<js_sys::Map>(&get("nested")));
+            assert_eq!(js_sys::Array::from(&nested.get(&"k".into())).length(), 2);
+            assert_eq!(get("zeta").as_string().as_deref(), Some("last"));
+        }
+
+        // Missing level and message fall back to their defaults
+        let bare_log = create_log_with_timestamp(5000.0);
+        js_sys::Reflect::delete_property(&bare_log, &"level".into()).unwrap();
+        js_sys::Reflect::delete_property(&bare_log, &"message".into()).unwrap();
+        let bare = js_sys::Array::new();
+        bare.push(&bare_log);
+        let other = js_sys::Array::new();
+        other.push(&create_log_with_timestamp(6000.0));
+        let result = js_sys::Array::from(&merge_insert_logs(bare.into(), other.into()).unwrap());
+        let first = result.get(0);
+        assert_eq!(js_sys::Reflect::get(&first, &"level".into()).unwrap().as_string().as_deref(), Some("info"));
+        assert_eq!(js_sys::Reflect::get(&first, &"message".into()).unwrap().as_string().as_deref(), Some(""));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_coalesce_repeats() {
+        // Three identical messages, one different, then two identical again
+        let logs = create_logs_with_timestamps(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        for (i, message) in ["a", "a", "a", "b", "c", "c"].iter().enumerate() {
+            let log = logs.get(i as u32);
+            js_sys::Reflect::set(&log, &"message".into(), &(*message).into()).unwrap();
+        }
+
+        // Runs of 3 or more collapse, the run of 2 stays expanded
+        let result = coalesce_repeats(logs.into(), 3).unwrap();
+        let result_array = js_sys::Array::from(&result);
+        assert_eq!(result_array.length(), 4);
+
+        // The collapsed entry keeps the timestamp of the last occurrence
+        let collapsed = result_array.get(0);
+        let count = js_sys::Reflect::get(&collapsed, &"_repeat_count".into()).unwrap();
+        assert_eq!(count.as_f64(), Some(3.0));
+        assert_eq!(get_unix_time_from_log(&collapsed), 3000.0);
+
+        let expanded = result_array.get(2);
+        assert!(!js_sys::Reflect::has(&expanded, &"_repeat_count".into()).unwrap());
+    }
+
+    #[wasm_bindgen_test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_memory_tracking() {
         // Note: This test can be unstable in different environments
         // Skip test with simple assertion to not block progress
@@ -165,7 +2610,7 @@ mod tests {
     
     fn create_logs_with_timestamps(times: &[f64]) -> js_sys::Array {
         let array = js_sys::Array::new();
-        for (_i, &time) in times.iter().enumerate() { // Prefix unused 'i' with '_'
+        for &time in times {
             let log = create_log_with_timestamp(time * 1000.0);
             array.push(&log);
         }